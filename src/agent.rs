@@ -1,28 +1,121 @@
+use semver::Version;
 use std::cmp::Ordering;
-use strum::{AsRefStr, Display, EnumIs, EnumString};
+use std::fs;
+use std::path::{Path, PathBuf};
+use strum::{AsRefStr, Display, EnumIs, EnumIter, EnumString};
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumString, EnumIs)]
+#[derive(
+  Copy, Clone, Debug, PartialEq, Eq, Hash, AsRefStr, Display, EnumIter, EnumString, EnumIs,
+)]
 #[strum(serialize_all = "UPPERCASE")]
 pub enum Agent {
+  Bun,
   Cargo,
+  Composer,
+  Dart,
+  Deno,
+  Dotnet,
+  Extension,
+  File,
+  Go,
+  Gradle,
+  Helm,
+  Maven,
   Npm,
   Pnpm,
+  Python,
   Tauri,
+  Yarn,
 }
 
 impl Agent {
   pub fn is_node(self) -> bool {
-    self.is_npm() || self.is_pnpm()
+    self.is_bun() || self.is_npm() || self.is_pnpm() || self.is_yarn()
   }
 
-  pub fn lockfile(&self) -> Option<&str> {
+  /// Parses the `packageManager` field of a `package.json`, e.g. `pnpm@9.0.0`.
+  pub fn from_package_manager(value: &str) -> Option<Self> {
+    let (name, _) = value.split_once('@')?;
+    match name {
+      "bun" => Some(Self::Bun),
+      "npm" => Some(Self::Npm),
+      "pnpm" => Some(Self::Pnpm),
+      "yarn" => Some(Self::Yarn),
+      _ => None,
+    }
+  }
+
+  /// Detects the package manager from the lockfile found at `dir`.
+  pub fn from_lockfile(dir: &Path) -> Option<Self> {
+    [Self::Bun, Self::Pnpm, Self::Yarn, Self::Npm]
+      .into_iter()
+      .find(|agent| agent.find_lockfile(dir).is_some())
+  }
+
+  /// Arguments used to install dependencies after the manifests at `dir` are updated.
+  pub fn install_args(self, dir: &Path) -> &'static [&'static str] {
     match self {
-      Self::Cargo => Some("Cargo.lock"),
-      Self::Npm => Some("package-lock.json"),
-      Self::Pnpm => Some("pnpm-lock.yaml"),
-      Self::Tauri => None,
+      // Berry enables `--immutable` on CI, which would reject the updated lockfile.
+      Self::Yarn if is_yarn_berry(dir) => &["install", "--no-immutable"],
+      Self::Yarn => &["install", "--non-interactive"],
+      // Without it, the lockfile would not pick up the new versions.
+      Self::Dotnet => &["restore", "--force-evaluate"],
+      _ => &["install"],
     }
   }
+
+  /// Lockfiles the agent may use, from the most to the least recent format.
+  pub fn lockfiles(self) -> &'static [&'static str] {
+    match self {
+      // https://bun.sh/docs/install/lockfile
+      Self::Bun => &["bun.lock", "bun.lockb"],
+      Self::Cargo => &["Cargo.lock"],
+      Self::Composer => &["composer.lock"],
+      Self::Dart => &["pubspec.lock"],
+      Self::Deno => &["deno.lock"],
+      // https://learn.microsoft.com/nuget/consume-packages/package-references-in-project-files#locking-dependencies
+      Self::Dotnet => &["packages.lock.json"],
+      Self::Go => &["go.sum"],
+      Self::Gradle => &["gradle.lockfile"],
+      Self::Helm => &["Chart.lock"],
+      Self::Npm => &["package-lock.json"],
+      Self::Pnpm => &["pnpm-lock.yaml"],
+      Self::Python => &["uv.lock", "poetry.lock", "pdm.lock"],
+      Self::Extension | Self::File | Self::Maven | Self::Tauri => &[],
+      Self::Yarn => &["yarn.lock"],
+    }
+  }
+
+  /// Returns the lockfile of this agent found at `dir`, if any.
+  pub fn find_lockfile(self, dir: &Path) -> Option<PathBuf> {
+    self
+      .lockfiles()
+      .iter()
+      .map(|it| dir.join(it))
+      .find(|it| matches!(it.try_exists(), Ok(true)))
+  }
+}
+
+/// Yarn 2+ (Berry) is identified by its `packageManager` version or by a `.yarnrc.yml`.
+fn is_yarn_berry(dir: &Path) -> bool {
+  let version = fs::read_to_string(dir.join("package.json"))
+    .ok()
+    .and_then(|it| serde_json::from_str::<serde_json::Value>(&it).ok())
+    .and_then(|it| {
+      it.get("packageManager")?
+        .as_str()
+        .map(ToOwned::to_owned)
+    })
+    .and_then(|it| {
+      let version = it.strip_prefix("yarn@")?;
+      let version = version.split('+').next()?;
+      Version::parse(version).ok()
+    });
+
+  match version {
+    Some(version) => version.major >= 2,
+    None => matches!(dir.join(".yarnrc.yml").try_exists(), Ok(true)),
+  }
 }
 
 impl PartialOrd for Agent {
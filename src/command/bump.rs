@@ -4,22 +4,24 @@ use colored::Colorize;
 use inquire::{Confirm, MultiSelect, Select};
 use itertools::Itertools;
 use serde::Deserialize;
-use std::fmt;
+use std::collections::HashSet;
 use std::path::PathBuf;
-use std::sync::OnceLock;
+use std::sync::{Mutex, OnceLock};
+use std::{fmt, mem};
 use strum::IntoEnumIterator;
 use tokio::process::Command;
 
 use super::{Choice, Commit, PromptResult};
 use crate::agent::Agent;
-use crate::package::Package;
-use crate::package::manifest::DEFAULT_VERSION;
+use crate::package::{DependentEdit, FollowerEdit, Package, PackageDisplay};
 use crate::release::Release;
-use crate::version::VersionExt;
 use crate::{impl_commit, search_packages};
 
 static RELEASE: OnceLock<Release> = OnceLock::new();
 
+/// Tags of the bumped packages whose manifests have no version field.
+static TAGS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
 #[derive(Args, Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct Bump {
@@ -51,7 +53,7 @@ pub struct Bump {
   #[arg(short = 'k', long)]
   no_ask: bool,
 
-  /// Do not commit the modified packages.
+  /// Do not commit the modified packages, nor tag them.
   #[arg(short = 't', long)]
   no_commit: bool,
 
@@ -74,6 +76,10 @@ pub struct Bump {
   /// Prerelease identifier.
   #[arg(long, value_name = "IDENTIFIER")]
   pre: Option<String>,
+
+  /// Only bump workspace roots and their members.
+  #[arg(short = 'w', long)]
+  workspace: bool,
 }
 
 impl_commit!(Bump);
@@ -82,25 +88,32 @@ impl super::Command for Bump {
   async fn execute(mut self) -> Result<()> {
     self.set_release()?;
 
-    let packages = search_packages!(&self)
-      .into_iter()
-      .filter(|it| it.version != DEFAULT_VERSION)
+    // Packages without a version may still depend on the ones being bumped.
+    let all = search_packages!(&self);
+    let packages = all
+      .iter()
+      .filter(|it| it.is_bumpable())
+      .filter(|it| check_bump(it))
       .collect_vec();
 
-    preview(&packages);
+    preview(&packages, &all);
 
     if self.dry_run {
       return Ok(());
     }
 
     if self.no_ask {
-      bump_all(packages).await?;
-    } else if let PromptResult::Abort = prompt(packages).await? {
+      bump_all(&packages, &all).await?;
+    } else if let PromptResult::Abort = prompt(&packages, &all).await? {
       return Ok(());
     }
 
+    // Tags would otherwise point at a commit without the new versions.
     if !self.no_commit {
       self.commit("chore: bump version").await?;
+
+      let tags = mem::take(&mut *TAGS.lock().unwrap());
+      self.tag(&tags).await?;
     }
 
     Ok(())
@@ -130,19 +143,54 @@ impl Bump {
 
     Ok(())
   }
+
+  /// Tags the bump commit, pushing the tags along with it.
+  async fn tag(&self, tags: &[String]) -> Result<()> {
+    use crate::git::{self, Git};
+
+    if tags.is_empty() {
+      return Ok(());
+    }
+
+    for tag in tags {
+      git::Tag::new(tag).spawn().await?;
+    }
+
+    if !self.no_push {
+      git::Push::new().follow_tags().spawn().await?;
+    }
+
+    Ok(())
+  }
+}
+
+/// Whether `package` can be bumped, warning about it otherwise.
+fn check_bump(package: &Package) -> bool {
+  let release = RELEASE.get().unwrap();
+  let Err(err) = package.check_bump(release) else {
+    return true;
+  };
+
+  let warning = "skipped:".bright_yellow();
+  println!("{} {warning} {err}", package.display());
+  false
 }
 
-async fn bump_all(packages: Vec<Package>) -> Result<()> {
+async fn bump_all(packages: &[&Package], all: &[Package]) -> Result<()> {
   let release = RELEASE.get().unwrap();
   let agents = packages
     .iter()
-    .map(Package::agent)
+    .map(|it| it.agent())
     .unique()
     .collect_vec();
 
-  packages
-    .into_iter()
-    .try_for_each(|package| package.bump(release))?;
+  let (edits, followers) = edits(packages, all);
+
+  let tags = bump_versions(packages, release)?;
+  TAGS.lock().unwrap().extend(tags);
+
+  DependentEdit::apply(&edits)?;
+  FollowerEdit::apply(&followers)?;
 
   // https://doc.rust-lang.org/cargo/commands/cargo-update.html#update-options
   if agents.contains(&Agent::Cargo) {
@@ -153,53 +201,103 @@ async fn bump_all(packages: Vec<Package>) -> Result<()> {
       .await?;
   }
 
+  // Modules requiring a bumped one need its checksum in their `go.sum`.
+  let modules = edits
+    .iter()
+    .map(|it| it.dependent)
+    .filter(|it| it.agent().is_go())
+    .unique_by(|it| &it.path);
+
+  for package in modules {
+    Command::new("go")
+      .args(["mod", "tidy"])
+      .current_dir(package.root_dir())
+      .spawn()?
+      .wait()
+      .await?;
+  }
+
   Ok(())
 }
 
-async fn prompt(mut packages: Vec<Package>) -> Result<PromptResult> {
-  if packages.len() == 1 {
-    let package = packages.swap_remove(0);
-    prompt_one(package)
+/// Bumps the version of each package, returning the tags to create.
+fn bump_versions(packages: &[&Package], release: &Release) -> Result<Vec<String>> {
+  // Members inheriting their version from the same workspace must only bump it once.
+  let mut sources = HashSet::with_capacity(packages.len());
+  let mut tags = Vec::new();
+  for package in packages {
+    if sources.insert(package.version_source()) {
+      package.bump(release)?;
+    }
+
+    tags.extend(package.tag(release));
+  }
+
+  Ok(tags)
+}
+
+fn edits<'a>(
+  packages: &[&Package],
+  all: &'a [Package],
+) -> (Vec<DependentEdit<'a>>, Vec<FollowerEdit<'a>>) {
+  let release = RELEASE.get().unwrap();
+  let bumped = packages
+    .iter()
+    .map(|it| (*it, it.next_version(release)))
+    .collect_vec();
+
+  (
+    DependentEdit::find(&bumped, all),
+    FollowerEdit::find(&bumped, all),
+  )
+}
+
+async fn prompt(packages: &[&Package], all: &[Package]) -> Result<PromptResult> {
+  if let [package] = packages {
+    prompt_one(package, all).await
   } else {
-    prompt_many(packages).await
+    prompt_many(packages, all).await
   }
 }
 
-fn prompt_one(package: Package) -> Result<PromptResult> {
+async fn prompt_one(package: &Package, all: &[Package]) -> Result<PromptResult> {
   let message = format!("Bump {}?", package.name);
   let should_bump = Confirm::new(&message)
     .with_default(true)
     .prompt()?;
 
   if should_bump {
-    let release = RELEASE.get().unwrap();
-    package.bump(release)?;
+    bump_all(&[package], all).await?;
     Ok(PromptResult::Continue)
   } else {
     Ok(PromptResult::Abort)
   }
 }
 
-async fn prompt_many(packages: Vec<Package>) -> Result<PromptResult> {
+async fn prompt_many(packages: &[&Package], all: &[Package]) -> Result<PromptResult> {
   let options = Choice::iter().collect_vec();
   let choice = Select::new("Bump packages?", options).prompt()?;
 
   match choice {
     Choice::All => {
-      bump_all(packages).await?;
+      bump_all(packages, all).await?;
       Ok(PromptResult::Continue)
     }
     Choice::Some => {
       let message = "Select the packages to bump.";
-      let packages = packages.into_iter().map(ChoiceWrapper).collect();
+      let packages = packages
+        .iter()
+        .copied()
+        .map(ChoiceWrapper)
+        .collect();
       let packages = MultiSelect::new(message, packages).prompt()?;
 
       if packages.is_empty() {
         println!("{}", "no package selected".truecolor(105, 105, 105));
         Ok(PromptResult::Abort)
       } else {
-        let packages = packages.into_iter().map(|it| it.0).collect();
-        bump_all(packages).await?;
+        let packages = packages.into_iter().map(|it| it.0).collect_vec();
+        bump_all(&packages, all).await?;
         Ok(PromptResult::Continue)
       }
     }
@@ -207,7 +305,7 @@ async fn prompt_many(packages: Vec<Package>) -> Result<PromptResult> {
   }
 }
 
-fn preview(packages: &[Package]) {
+fn preview(packages: &[&Package], all: &[Package]) {
   use tabled::builder::Builder;
   use tabled::settings::object::Segment;
   use tabled::settings::{Alignment, Modify, Style};
@@ -229,8 +327,7 @@ fn preview(packages: &[Package]) {
       .to_string();
 
     let new_version = package
-      .version
-      .with_release(release)
+      .next_version(release)
       .to_string()
       .bright_green()
       .to_string();
@@ -256,13 +353,145 @@ fn preview(packages: &[Package]) {
   table.with(Modify::new(new_version_col).with(Alignment::right()));
 
   println!("{table}");
+
+  let (edits, followers) = edits(packages, all);
+  preview_dependents(&edits);
+  preview_followers(&followers);
+}
+
+fn preview_dependents(edits: &[DependentEdit]) {
+  use tabled::builder::Builder;
+  use tabled::settings::object::Segment;
+  use tabled::settings::{Alignment, Modify, Panel, Style};
+
+  if edits.is_empty() {
+    return;
+  }
+
+  let mut builder = Builder::with_capacity(edits.len(), 6);
+
+  for edit in edits {
+    let agent = edit
+      .dependent
+      .agent()
+      .to_string()
+      .bright_magenta()
+      .bold();
+
+    let record = [
+      agent.to_string(),
+      edit.dependent.name.bold().to_string(),
+      edit.dependency.name.clone(),
+      edit
+        .dependency
+        .comparator
+        .to_string()
+        .bright_blue()
+        .to_string(),
+      "=>".to_string(),
+      edit
+        .comparator
+        .to_string()
+        .bright_green()
+        .to_string(),
+    ];
+
+    builder.push_record(record);
+  }
+
+  let mut table = builder.build();
+  let header = "dependents".bright_cyan().to_string();
+  table
+    .with(Style::blank())
+    .with(Panel::header(header));
+
+  let version_col = Segment::new(.., 3..4);
+  table.with(Modify::new(version_col).with(Alignment::right()));
+
+  let new_version_col = Segment::new(.., 5..6);
+  table.with(Modify::new(new_version_col).with(Alignment::right()));
+
+  println!("\n{table}");
+}
+
+fn preview_followers(edits: &[FollowerEdit]) {
+  use tabled::builder::Builder;
+  use tabled::settings::object::Segment;
+  use tabled::settings::{Alignment, Modify, Panel, Style};
+
+  if edits.is_empty() {
+    return;
+  }
+
+  let mut builder = Builder::with_capacity(edits.len(), 5);
+
+  for edit in edits {
+    let agent = edit
+      .follower
+      .agent()
+      .to_string()
+      .bright_magenta()
+      .bold();
+
+    let record = [
+      agent.to_string(),
+      edit.follower.name.bold().to_string(),
+      edit.leader.clone(),
+      "=>".to_string(),
+      edit
+        .version
+        .to_string()
+        .bright_green()
+        .to_string(),
+    ];
+
+    builder.push_record(record);
+  }
+
+  let mut table = builder.build();
+  let header = "followers".bright_cyan().to_string();
+  table
+    .with(Style::blank())
+    .with(Panel::header(header));
+
+  let version_col = Segment::new(.., 4..5);
+  table.with(Modify::new(version_col).with(Alignment::right()));
+
+  println!("\n{table}");
 }
 
-struct ChoiceWrapper(Package);
+struct ChoiceWrapper<'a>(&'a Package);
 
-impl fmt::Display for ChoiceWrapper {
+impl fmt::Display for ChoiceWrapper<'_> {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     let agent = self.0.agent().to_string();
     write!(f, "{agent}: {}", self.0.name)
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+  use semver::BuildMetadata;
+
+  #[test]
+  fn bumps_a_shared_workspace_version_once() {
+    let dir = TempDir::new();
+    let root =
+      "[workspace]\nmembers = [\"a\", \"b\"]\n\n[workspace.package]\nversion = \"1.2.3\"\n";
+    dir.write("Cargo.toml", root);
+
+    let member = |name: &str| format!("[package]\nname = \"{name}\"\nversion.workspace = true\n");
+    let a = Package::new(dir.write("a/Cargo.toml", &member("a"))).unwrap();
+    let b = Package::new(dir.write("b/Cargo.toml", &member("b"))).unwrap();
+    assert_eq!(a.version_source(), b.version_source());
+
+    let release = Release::Minor(BuildMetadata::EMPTY);
+    bump_versions(&[&a, &b], &release).unwrap();
+
+    assert_eq!(dir.read("Cargo.toml"), root.replace("1.2.3", "1.3.0"));
+    assert_eq!(dir.read("a/Cargo.toml"), member("a"));
+    assert_eq!(dir.read("b/Cargo.toml"), member("b"));
+  }
+}
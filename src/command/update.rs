@@ -1,4 +1,4 @@
-use anyhow::{Error, Result};
+use anyhow::{Error, Result, bail};
 use clap::Args;
 use colored::Colorize;
 use crossterm::{ExecutableCommand, cursor, terminal};
@@ -9,7 +9,8 @@ use serde::Deserialize;
 use std::io::{self, Write};
 use std::path::PathBuf;
 use std::sync::{Arc, Mutex, OnceLock};
-use std::{env, fmt, mem};
+use std::time::Duration;
+use std::{fmt, mem};
 use strum::IntoEnumIterator;
 use tokio::process::Command;
 use tokio::task::JoinSet;
@@ -18,6 +19,7 @@ use super::{Choice, Commit, PromptResult};
 use crate::agent::Agent;
 use crate::dependency::{Dependency, DependencyTree};
 use crate::package::{GlobalPackage, Package, PackageDependencyTree, PackageDisplay};
+use crate::registry::{DiskCache, Registries};
 use crate::release::Release;
 use crate::version::ComparatorExt;
 use crate::{command, impl_commit, search_packages};
@@ -26,6 +28,9 @@ type TreeTuple<T> = (T, DependencyTree);
 
 static RELEASE: OnceLock<Option<Release>> = OnceLock::new();
 
+/// Ten minutes, after which the registries are asked whether the versions changed.
+const DEFAULT_CACHE_TTL: u64 = 600;
+
 #[derive(Args, Debug, Default, Deserialize)]
 #[serde(default)]
 pub struct Update {
@@ -44,6 +49,10 @@ pub struct Update {
   #[arg(short = 'm', long, value_name = "MESSAGE")]
   commit_message: Option<String>,
 
+  /// Seconds the cached versions are used for before asking the registry again.
+  #[arg(long, value_name = "SECONDS")]
+  cache_ttl: Option<u64>,
+
   /// Dependencies to update.
   #[arg(short = 'D', long, value_name = "DEPENDENCY")]
   dependency: Option<Vec<String>>,
@@ -60,6 +69,10 @@ pub struct Update {
   #[arg(short = 'k', long)]
   no_ask: bool,
 
+  /// Only use the versions cached by previous runs.
+  #[arg(long)]
+  offline: bool,
+
   /// Do not commit the modified packages.
   #[arg(short = 't', long)]
   no_commit: bool,
@@ -80,6 +93,15 @@ pub struct Update {
   #[arg(short = 'p', long, value_name = "PATH", default_value = ".")]
   path: Option<Vec<PathBuf>>,
 
+  /// Read versions from the files at `DIR` instead of the public registries.
+  #[arg(long, value_name = "DIR")]
+  registry: Option<PathBuf>,
+
+  /// Registry of each agent, used to fetch the versions of the dependencies.
+  #[arg(skip)]
+  #[serde(skip)]
+  registries: Registries,
+
   /// Whether to only update peer dependencies.
   #[arg(long)]
   peer: bool,
@@ -91,6 +113,10 @@ pub struct Update {
   /// Skip updating dependencies.
   #[arg(short = 'S', long, value_name = "DEPENDENCY")]
   skip_dependency: Option<Vec<String>>,
+
+  /// Only update workspace roots and their members.
+  #[arg(short = 'w', long)]
+  workspace: bool,
 }
 
 impl_commit!(Update);
@@ -99,6 +125,17 @@ impl super::Command for Update {
   async fn execute(mut self) -> Result<()> {
     self.set_release();
 
+    if let Some(dir) = &self.registry {
+      self.registries = Registries::local(dir);
+    }
+
+    let ttl = self.cache_ttl.unwrap_or(DEFAULT_CACHE_TTL);
+    match DiskCache::new(Duration::from_secs(ttl), self.offline) {
+      Some(cache) => cache.install(),
+      None if self.offline => bail!("no cache directory found to work offline"),
+      None => {}
+    }
+
     if self.global {
       self.execute_global().await
     } else {
@@ -191,9 +228,10 @@ impl Update {
     for package in packages {
       let trees = Arc::clone(&trees);
       let cache = Arc::clone(&cache);
+      let registries = self.registries.clone();
       set.spawn(async move {
         let mut tree = package.dependency_tree();
-        tree.fetch(cache).await?;
+        tree.fetch(cache, &registries).await?;
 
         let mut trees = trees.lock().unwrap();
         trees.push((package, tree));
@@ -211,9 +249,14 @@ impl Update {
 
     clear_line()?;
 
-    let mut trees = Arc::into_inner(trees)
+    let trees = Arc::into_inner(trees)
       .expect("arc has unexpected strong references")
-      .into_inner()?
+      .into_inner()?;
+
+    warn_uncached(&trees);
+    warn_unresolved(&trees);
+
+    let mut trees = trees
       .into_iter()
       .filter_map(|(package, tree)| self.filter_tree(package, tree))
       .collect_vec();
@@ -274,19 +317,51 @@ async fn update_local(trees: Vec<TreeTuple<Package>>) -> Result<()> {
     .unique()
     .collect_vec();
 
+  // Each workspace has a single lockfile, so we install once per workspace root.
+  let installs = trees
+    .iter()
+    .filter(|(package, _)| {
+      let agent = package.agent();
+      agent.is_node() || agent.is_deno() || agent.is_dotnet()
+    })
+    .map(|(package, _)| (package.agent(), package.root_dir().to_path_buf()))
+    .unique()
+    .collect_vec();
+
+  let tidy = trees
+    .iter()
+    .filter(|(package, _)| package.agent().is_go())
+    .map(|(package, _)| package.root_dir().to_path_buf())
+    .unique()
+    .collect_vec();
+
+  // Only the updated packages are passed to Composer, so it leaves the others locked.
+  let composer = trees
+    .iter()
+    .filter(|(package, _)| package.agent().is_composer())
+    .map(|(package, tree)| {
+      let names = tree
+        .dependencies
+        .iter()
+        .filter(|it| it.as_target(release).is_some())
+        .map(|it| it.name.clone())
+        .collect_vec();
+
+      (package.root_dir().to_path_buf(), names)
+    })
+    .filter(|(_, names)| !names.is_empty())
+    .collect_vec();
+
   for (package, tree) in trees {
     package.update(&tree, release)?;
   }
 
-  if let Some(agent) = agents.iter().find(|it| it.is_node()) {
-    let cwd = env::current_dir()?;
-    let lockfile = agent.lockfile().unwrap();
-    let lockfile = cwd.join(lockfile);
-
-    if let Ok(true) = lockfile.try_exists() {
+  for (agent, dir) in installs {
+    if agent.find_lockfile(&dir).is_some() {
       let program = agent.to_string().to_lowercase();
       command!(&program)
-        .arg("install")
+        .args(agent.install_args(&dir))
+        .current_dir(&dir)
         .spawn()?
         .wait()
         .await?;
@@ -301,6 +376,27 @@ async fn update_local(trees: Vec<TreeTuple<Package>>) -> Result<()> {
       .await?;
   }
 
+  // https://getcomposer.org/doc/03-cli.md#update-u-upgrade
+  for (dir, names) in composer {
+    command!("composer")
+      .arg("update")
+      .args(names)
+      .current_dir(&dir)
+      .spawn()?
+      .wait()
+      .await?;
+  }
+
+  // https://go.dev/ref/mod#go-mod-tidy
+  for dir in tidy {
+    Command::new("go")
+      .args(["mod", "tidy"])
+      .current_dir(&dir)
+      .spawn()?
+      .wait()
+      .await?;
+  }
+
   Ok(())
 }
 
@@ -356,6 +452,35 @@ fn prompt(
   }
 }
 
+/// Lists the dependencies left out when offline, as nothing was cached for them.
+fn warn_uncached(trees: &[(impl PackageDisplay, DependencyTree)]) {
+  for (package, tree) in trees {
+    if tree.uncached.is_empty() {
+      continue;
+    }
+
+    let names = tree
+      .uncached
+      .iter()
+      .map(|it| it.name.as_str())
+      .sorted_unstable()
+      .join(", ");
+
+    let warning = "no cached versions for".bright_yellow();
+    println!("{} {warning} {names}", package.display());
+  }
+}
+
+/// Lists the dependencies left out as their registry could not be found.
+fn warn_unresolved(trees: &[(impl PackageDisplay, DependencyTree)]) {
+  for (package, tree) in trees {
+    for (name, reason) in &tree.unresolved {
+      let warning = format!("skipped {name}:").bright_yellow();
+      println!("{} {warning} {reason}", package.display());
+    }
+  }
+}
+
 fn preview(trees: &[(impl PackageDisplay, DependencyTree)]) {
   use tabled::builder::Builder;
   use tabled::settings::object::Segment;
@@ -373,9 +498,22 @@ fn preview(trees: &[(impl PackageDisplay, DependencyTree)]) {
         let comparator = &dependency.comparator;
         comparator.normalize(&mut target.comparator);
 
+        let mut name = dependency.name.clone();
+        if let Some(package) = &dependency.package {
+          let package = format!("({package})");
+          name = format!("{name} {}", package.truecolor(105, 105, 105));
+        }
+
+        let kind = dependency.kind.as_ref();
+        let kind = match &dependency.target {
+          Some(cfg) if kind.is_empty() => cfg.truecolor(105, 105, 105).to_string(),
+          Some(cfg) => format!("{} {}", kind.bright_cyan(), cfg.truecolor(105, 105, 105)),
+          None => kind.bright_cyan().to_string(),
+        };
+
         let mut record = vec![
-          dependency.name.clone(),
-          dependency.kind.as_ref().bright_cyan().to_string(),
+          name,
+          kind,
           comparator.to_string().bright_blue().to_string(),
           "=>".to_string(),
           target.to_string().bright_green().to_string(),
@@ -455,6 +593,57 @@ struct ChoiceWrapper(Dependency);
 
 impl fmt::Display for ChoiceWrapper {
   fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    write!(f, "{}", self.0.name)
+    write!(f, "{}", self.0.name)?;
+    if let Some(cfg) = &self.0.target {
+      write!(f, " ({cfg})")?;
+    }
+
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::command::Command as _;
+  use crate::testing::TempDir;
+
+  #[tokio::test]
+  async fn updates_manifests_with_the_injected_registries() {
+    let dir = TempDir::new();
+    let manifest = r#"{
+  "name": "app",
+  "version": "1.0.0",
+  "dependencies": {
+    "left-pad": "^1.0.0",
+    "right-pad": "~2.1.0"
+  }
+}
+"#;
+
+    dir.write("app/package.json", manifest);
+    dir.write(
+      "registry/npm/left-pad.json",
+      r#"{ "versions": ["1.0.0", "1.3.0", "2.0.0"] }"#,
+    );
+    dir.write(
+      "registry/npm/right-pad.json",
+      r#"{ "versions": ["2.1.0", "2.1.4", "2.2.0"] }"#,
+    );
+
+    let update = Update {
+      path: Some(vec![dir.path().join("app")]),
+      registries: Registries::local(&dir.path().join("registry")),
+      no_ask: true,
+      no_commit: true,
+      ..Update::default()
+    };
+
+    update.execute().await.unwrap();
+
+    let expected = manifest
+      .replace("^1.0.0", "^1.3.0")
+      .replace("~2.1.0", "~2.1.4");
+    assert_eq!(dir.read("app/package.json"), expected);
   }
 }
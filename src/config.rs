@@ -0,0 +1,64 @@
+use anyhow::Result;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Settings read from a `miho.toml`, either at the root of a search path or next to a manifest.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Config {
+  pub version_file: Vec<VersionFileConfig>,
+  pub web_extension: WebExtensionConfig,
+  pub helm_chart: HelmChartConfig,
+}
+
+impl Config {
+  pub const FILENAME: &str = "miho.toml";
+
+  /// Reads the config at `dir`, if there is one.
+  pub fn read(dir: &Path) -> Result<Self> {
+    let path = dir.join(Self::FILENAME);
+    if !path.is_file() {
+      return Ok(Self::default());
+    }
+
+    let contents = fs::read_to_string(path)?;
+    toml::from_str(&contents).map_err(Into::into)
+  }
+}
+
+/// Files holding a version outside of any manifest, such as an install snippet in a `README.md`.
+///
+/// ```toml
+/// [[version-file]]
+/// path = "README.md"
+/// pattern = 'miho = "(.+)"'
+/// source = "Cargo.toml"
+/// ```
+#[derive(Debug, Deserialize)]
+pub struct VersionFileConfig {
+  /// Glob matching the files, relative to the config.
+  pub path: String,
+  /// Regex whose first capture group is the version. Defaults to the first word of the file.
+  pub pattern: Option<String>,
+  /// Manifest of the package whose version the files follow, relative to the config.
+  /// Without it, each file is a package of its own.
+  pub source: Option<PathBuf>,
+  pub name: Option<String>,
+}
+
+/// Browser extension `manifest.json` next to the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct WebExtensionConfig {
+  /// Whether the extension follows the version of the sibling `package.json`.
+  pub follow_package_json: bool,
+}
+
+/// Helm `Chart.yaml` next to the config.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct HelmChartConfig {
+  /// Manifest whose version `appVersion` follows, relative to the config.
+  pub app_version_from: Option<PathBuf>,
+}
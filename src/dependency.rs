@@ -1,48 +1,75 @@
-use anyhow::{Result, bail};
-use itertools::Itertools;
-use reqwest::Client;
-use reqwest::header::ACCEPT;
+use anyhow::{Error, Result};
 use semver::{Comparator, Version, VersionReq};
-use serde_json::Value;
 use std::cmp::Ordering;
 use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-use std::sync::{Arc, LazyLock, Mutex};
+use std::sync::{Arc, Mutex};
 use std::{fmt, mem};
 use strum::{AsRefStr, Display, EnumIs, EnumString};
 use tokio::task::JoinSet;
 
 use crate::agent::Agent;
+use crate::registry::{NotCached, Registries, Token};
 use crate::release::Release;
 use crate::return_if_ne;
 use crate::version::{ComparatorExt, VersionExt, VersionReqExt};
 
 pub type Cache = HashSet<DependencyCache>;
 
-const CARGO_REGISTRY: &str = "https://crates.io/api/v1/crates";
-const NPM_REGISTRY: &str = "https://registry.npmjs.org";
-
-const USER_AGENT: &str = concat!("miho/", env!("CARGO_PKG_VERSION"));
-
-static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
-  Client::builder()
-    .use_rustls_tls()
-    .user_agent(USER_AGENT)
-    .brotli(true)
-    .gzip(true)
-    .build()
-    .expect("failed to create http client")
-});
-
 #[derive(Debug)]
 pub struct Dependency {
+  /// Name of the dependency in the manifest, which may be an alias.
   pub name: String,
+  /// Name of the package in the registry, if different from `name`.
+  pub package: Option<String>,
   pub comparator: Comparator,
+  /// Exclusive bound the comparator cannot express, such as the `<1` of a PEP 440 `~=0.5`.
+  pub upper_bound: Option<Version>,
   pub kind: DependencyKind,
+  /// Agent whose registry hosts the dependency, if it differs from the one of the tree.
+  pub registry: Option<Agent>,
+  /// Base URL of the registry, for agents without a default one, such as Helm,
+  /// or when it is not the default one, such as a private Cargo registry.
+  pub registry_url: Option<String>,
+  /// Value of the `Authorization` header sent to the registry at `registry_url`.
+  pub registry_token: Option<Token>,
+  /// Section of the manifest this dependency belongs to within its kind,
+  /// such as a `cfg(windows)` platform or a named pnpm catalog.
+  pub target: Option<String>,
+  /// Features enabled by the dependent, which a newer version must still provide.
+  pub features: Vec<String>,
+  /// Minimum Rust version supported by the dependent, which a newer version must not raise.
+  pub rust_version: Option<Version>,
   versions: Vec<Version>,
 }
 
 impl Dependency {
+  /// Name under which the dependency is published in the registry.
+  pub fn registry_name(&self) -> &str {
+    self.package.as_deref().unwrap_or(&self.name)
+  }
+
+  /// Key under which the versions of the dependency are cached.
+  /// Requirements that may filter out versions are part of it.
+  fn cache_key(&self) -> String {
+    let mut key = match &self.registry_url {
+      Some(url) => format!("{url}#{}", self.registry_name()),
+      None => self.registry_name().to_owned(),
+    };
+
+    if let Some(rust_version) = &self.rust_version {
+      key.push('@');
+      key.push_str(&rust_version.to_string());
+    }
+
+    for feature in &self.features {
+      key.push('+');
+      key.push_str(feature);
+    }
+
+    key
+  }
+
   pub fn latest(&self) -> Option<&Version> {
     self
       .versions
@@ -50,24 +77,30 @@ impl Dependency {
       .max_by(|a, b| Version::cmp_precedence(a, b))
   }
 
-  pub fn latest_with_req(&self, requirement: &VersionReq) -> Option<&Version> {
+  pub fn latest_with_req(
+    &self,
+    requirement: &VersionReq,
+    upper_bound: Option<&Version>,
+  ) -> Option<&Version> {
     self
       .versions
       .iter()
       .filter(|v| requirement.matches_any(v))
+      .filter(|v| upper_bound.is_none_or(|bound| v.cmp_precedence(bound).is_lt()))
       .max_by(|a, b| Version::cmp_precedence(a, b))
   }
 
   pub fn as_target(&self, release: Option<&Release>) -> Option<Target<'_>> {
     let comparator = &self.comparator;
-    let requirement = if let Some(it) = release {
-      comparator.with_release(it).as_version_req()
+    // A release picks its own range, which may go past the upper bound.
+    let (requirement, upper_bound) = if let Some(it) = release {
+      (comparator.with_release(it).as_version_req(), None)
     } else {
-      comparator.as_version_req()
+      (comparator.as_version_req(), self.upper_bound.as_ref())
     };
 
     let mut target_cmp = self
-      .latest_with_req(&requirement)
+      .latest_with_req(&requirement, upper_bound)
       .and_then(|version| {
         let target_cmp = version.as_comparator(comparator.op);
         (target_cmp != *comparator).then_some(target_cmp)
@@ -85,7 +118,11 @@ impl Dependency {
 
 impl PartialEq for Dependency {
   fn eq(&self, other: &Self) -> bool {
-    self.name == other.name && self.comparator == other.comparator && self.kind == other.kind
+    self.name == other.name
+      && self.package == other.package
+      && self.comparator == other.comparator
+      && self.kind == other.kind
+      && self.target == other.target
   }
 }
 
@@ -100,7 +137,8 @@ impl PartialOrd for Dependency {
 impl Ord for Dependency {
   fn cmp(&self, other: &Self) -> Ordering {
     return_if_ne!(self.kind.cmp(&other.kind));
-    self.name.cmp(&other.name)
+    return_if_ne!(self.name.cmp(&other.name));
+    self.target.cmp(&other.target)
   }
 }
 
@@ -130,22 +168,61 @@ impl Hash for DependencyCache {
 pub struct DependencyTree {
   pub agent: Agent,
   pub dependencies: Vec<Dependency>,
+  /// Dependencies left out when offline, as nothing was cached for them.
+  pub uncached: Vec<Dependency>,
+  /// Dependencies left out as their registry could not be found, with the reason why.
+  pub unresolved: Vec<(String, String)>,
 }
 
 impl DependencyTree {
   pub fn new(agent: Agent) -> Self {
-    Self { agent, dependencies: Vec::new() }
+    Self {
+      agent,
+      dependencies: Vec::new(),
+      uncached: Vec::new(),
+      unresolved: Vec::new(),
+    }
+  }
+
+  pub fn add(
+    &mut self,
+    name: impl AsRef<str>,
+    comparator: Comparator,
+    kind: DependencyKind,
+  ) -> &mut Dependency {
+    self.add_renamed(name, None::<&str>, comparator, kind)
   }
 
-  pub fn add(&mut self, name: impl AsRef<str>, comparator: Comparator, kind: DependencyKind) {
+  /// Add a dependency whose name in the manifest may differ from its name in the registry.
+  pub fn add_renamed(
+    &mut self,
+    name: impl AsRef<str>,
+    package: Option<impl AsRef<str>>,
+    comparator: Comparator,
+    kind: DependencyKind,
+  ) -> &mut Dependency {
+    let name = name.as_ref();
+    let package = package
+      .map(|it| it.as_ref().to_owned())
+      .filter(|it| it != name);
+
     let dependency = Dependency {
-      name: name.as_ref().to_owned(),
+      name: name.to_owned(),
+      package,
       comparator,
+      upper_bound: None,
       kind,
+      registry: None,
+      registry_url: None,
+      registry_token: None,
+      target: None,
+      features: Vec::new(),
+      rust_version: None,
       versions: Vec::new(),
     };
 
     self.dependencies.push(dependency);
+    self.dependencies.last_mut().unwrap()
   }
 
   /// Add dependencies to the tree.
@@ -163,42 +240,49 @@ impl DependencyTree {
     }
   }
 
-  /// Update the dependency tree, fetching metadata from the registry.
-  pub async fn fetch(&mut self, cache: Arc<Mutex<Cache>>) -> Result<()> {
+  /// Update the dependency tree, fetching metadata from the registry of each dependency.
+  pub async fn fetch(&mut self, cache: Arc<Mutex<Cache>>, registries: &Registries) -> Result<()> {
     let mut set = JoinSet::new();
 
     let dependencies = mem::take(&mut self.dependencies);
     self.dependencies.reserve(dependencies.len());
 
     for mut dependency in dependencies {
-      let agent = self.agent;
+      let agent = dependency.registry.unwrap_or(self.agent);
       let cache = Arc::clone(&cache);
 
       {
         let cache = cache.lock().unwrap();
-        if let Some(cached) = Self::find_cached(&cache, &dependency.name, agent) {
+        if let Some(cached) = Self::find_cached(&cache, &dependency.cache_key(), agent) {
           dependency.versions.clone_from(&cached.versions);
           self.dependencies.push(dependency);
           continue;
         }
       }
 
+      let registry = registries.get(agent)?;
       set.spawn(async move {
-        dependency.versions = match agent {
-          Agent::Cargo => Self::fetch_cargo(&dependency, agent, cache).await?,
-          Agent::Npm | Agent::Pnpm => Self::fetch_npm(&dependency, agent, cache).await?,
-          Agent::Tauri => bail!("tauri is not a package manager"),
+        dependency.versions = match registry.versions(&dependency).await {
+          Ok(versions) => versions,
+          Err(err) if err.is::<NotCached>() => return Ok((dependency, false)),
+          Err(err) => return Err(err),
         };
 
         dependency.versions.shrink_to_fit();
 
-        Ok(dependency)
+        let mut cache = cache.lock().unwrap();
+        let key = dependency.cache_key();
+        Self::add_to_cache(&mut cache, &key, agent, &dependency.versions);
+
+        Ok::<_, Error>((dependency, true))
       });
     }
 
     while let Some(result) = set.join_next().await {
-      let dependency = result??;
-      if !dependency.versions.is_empty() {
+      let (dependency, is_cached) = result??;
+      if !is_cached {
+        self.uncached.push(dependency);
+      } else if !dependency.versions.is_empty() {
         self.dependencies.push(dependency);
       }
     }
@@ -208,90 +292,6 @@ impl DependencyTree {
     Ok(())
   }
 
-  /// <https://doc.rust-lang.org/cargo/reference/registry-web-api.html>
-  async fn fetch_cargo(
-    dependency: &Dependency,
-    agent: Agent,
-    cache: Arc<Mutex<Cache>>,
-  ) -> Result<Vec<Version>> {
-    let url = format!("{CARGO_REGISTRY}/{}/versions", dependency.name);
-    let response = HTTP_CLIENT.get(&url).send().await?;
-
-    let json: Value = response.json().await?;
-    let Some(versions) = json.get("versions").and_then(Value::as_array) else {
-      bail!("no versions found for {}", dependency.name);
-    };
-
-    let versions = versions
-      .iter()
-      .filter_map(Self::parse_cargo_version)
-      .collect_vec();
-
-    let mut cache = cache.lock().unwrap();
-    Self::add_to_cache(&mut cache, &dependency.name, agent, &versions);
-
-    Ok(versions)
-  }
-
-  fn parse_cargo_version(version: &Value) -> Option<Version> {
-    if version
-      .get("yanked")
-      .and_then(Value::as_bool)
-      .eq(&Some(true))
-    {
-      return None;
-    }
-
-    version
-      .get("num")
-      .and_then(Value::as_str)
-      .and_then(|it| Version::parse(it).ok())
-  }
-
-  /// <https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md>
-  async fn fetch_npm(
-    dependency: &Dependency,
-    agent: Agent,
-    cache: Arc<Mutex<Cache>>,
-  ) -> Result<Vec<Version>> {
-    let url = format!("{NPM_REGISTRY}/{}", dependency.name);
-    let response = HTTP_CLIENT
-      .get(&url)
-      .header(ACCEPT, "application/vnd.npm.install-v1+json")
-      .send()
-      .await?;
-
-    let json: Value = response.json().await?;
-    let Some(versions) = json.get("versions").and_then(Value::as_object) else {
-      bail!("no versions found for {}", dependency.name);
-    };
-
-    let versions = versions
-      .values()
-      .filter_map(Self::parse_npm_version)
-      .collect_vec();
-
-    let mut cache = cache.lock().unwrap();
-    Self::add_to_cache(&mut cache, &dependency.name, agent, &versions);
-
-    Ok(versions)
-  }
-
-  fn parse_npm_version(version: &Value) -> Option<Version> {
-    if version
-      .get("deprecated")
-      .and_then(Value::as_str)
-      .is_some_and(|it| !it.is_empty())
-    {
-      return None;
-    }
-
-    version
-      .get("version")
-      .and_then(Value::as_str)
-      .and_then(|it| Version::parse(it).ok())
-  }
-
   fn add_to_cache(cache: &mut Cache, name: &str, agent: Agent, versions: &[Version]) {
     if Self::find_cached(cache, name, agent).is_none() {
       let dependency = DependencyCache {
@@ -315,12 +315,19 @@ impl DependencyTree {
 #[strum(serialize_all = "kebab-case")]
 pub enum DependencyKind {
   Build,
+  Catalog,
   #[strum(to_string = "dev")]
   Development,
+  Import,
+  /// Versions a Maven project imposes on its modules, from `<dependencyManagement>`.
+  Management,
   #[strum(to_string = "")]
   Normal,
+  Parent,
   Peer,
   PackageManager,
+  Resolution,
+  Workspace,
 }
 
 impl DependencyKind {
@@ -331,6 +338,12 @@ impl DependencyKind {
       DependencyKind::Build => 2,
       DependencyKind::Peer => 3,
       DependencyKind::PackageManager => 4,
+      DependencyKind::Workspace => 5,
+      DependencyKind::Management => 6,
+      DependencyKind::Catalog => 7,
+      DependencyKind::Resolution => 8,
+      DependencyKind::Import => 9,
+      DependencyKind::Parent => 10,
     }
   }
 }
@@ -1,6 +1,7 @@
 mod add;
 mod commit;
 mod push;
+mod tag;
 
 use anyhow::Result;
 use std::process::ExitStatus;
@@ -9,6 +10,7 @@ use strum::{Display, EnumString};
 pub use add::Add;
 pub use commit::Commit;
 pub use push::Push;
+pub use tag::Tag;
 
 pub trait Git {
   async fn spawn(&mut self) -> Result<ExitStatus>;
@@ -28,12 +30,14 @@ macro_rules! impl_git {
   };
 }
 
-impl_git!(Add, Commit, Push);
+impl_git!(Add, Commit, Push, Tag);
 
 #[derive(Display, EnumString)]
 #[strum(serialize_all = "kebab-case", prefix = "--")]
 pub enum Flag {
   All,
+  Annotate,
+  FollowTags,
   Message,
   NoVerify,
 }
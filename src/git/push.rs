@@ -1,3 +1,4 @@
+use super::Flag;
 use tokio::process::Command;
 
 /// <https://git-scm.com/docs/git-push>
@@ -13,6 +14,12 @@ impl Push {
       args: vec!["push".into()],
     }
   }
+
+  /// <https://git-scm.com/docs/git-push#Documentation/git-push.txt---follow-tags>
+  pub fn follow_tags(&mut self) -> &mut Self {
+    self.args.push(Flag::FollowTags.to_string());
+    self
+  }
 }
 
 impl Default for Push {
@@ -0,0 +1,25 @@
+use super::Flag;
+use tokio::process::Command;
+
+/// <https://git-scm.com/docs/git-tag>
+pub struct Tag {
+  pub(super) command: Command,
+  pub(super) args: Vec<String>,
+}
+
+impl Tag {
+  /// Creates an annotated tag, so it can be pushed with `--follow-tags`.
+  pub fn new<T: AsRef<str>>(name: T) -> Self {
+    let name = name.as_ref();
+    Self {
+      command: Command::new("git"),
+      args: vec![
+        "tag".into(),
+        Flag::Annotate.to_string(),
+        name.into(),
+        Flag::Message.to_string(),
+        name.into(),
+      ],
+    }
+  }
+}
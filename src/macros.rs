@@ -51,6 +51,10 @@ macro_rules! search_packages {
       builder = builder.agent(&agents);
     }
 
+    if $command.workspace {
+      builder = builder.workspace_only();
+    }
+
     builder.search()?
   }};
 }
@@ -1,12 +1,14 @@
-#![feature(try_blocks)]
-
 mod agent;
 mod command;
+mod config;
 mod dependency;
 mod git;
 mod macros;
 mod package;
+mod registry;
 mod release;
+#[cfg(test)]
+mod testing;
 mod version;
 
 use anyhow::Result;
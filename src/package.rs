@@ -1,19 +1,25 @@
+mod graph;
 pub mod manifest;
+mod npmrc;
 mod search;
+mod workspace;
 
 use anyhow::{Result, bail};
 use colored::Colorize;
 use itertools::Itertools;
-use manifest::{ManifestBox, ManifestKind};
+use manifest::{DEFAULT_VERSION, ManifestBox, ManifestKind, VersionFile};
+use npmrc::Npmrc;
 use semver::{Op, Version};
 use serde_json::Value;
 use std::cmp::Ordering;
-use std::fmt;
 use std::path::{Path, PathBuf};
+use std::{fmt, io};
 
+pub use graph::{DependentEdit, FollowerEdit};
 pub use search::SearchBuilder;
 
 use crate::agent::Agent;
+use crate::config::VersionFileConfig;
 use crate::dependency::{DependencyKind, DependencyTree};
 use crate::release::Release;
 use crate::version::VersionExt;
@@ -32,7 +38,16 @@ impl Package {
     let path = path.as_ref();
     let kind = ManifestKind::try_from(path)?;
     let manifest = kind.read(path)?;
+    Self::from_manifest(path, manifest)
+  }
+
+  /// Creates a representation of a version file matched by `config` from the `miho.toml` at `dir`.
+  pub fn from_version_file(path: &Path, config: &VersionFileConfig, dir: &Path) -> Result<Self> {
+    let manifest = VersionFile::read(path, config, dir)?;
+    Self::from_manifest(path, manifest)
+  }
 
+  fn from_manifest(path: &Path, manifest: ManifestBox) -> Result<Self> {
     let package = Self {
       name: manifest.name().to_owned(),
       version: manifest.version()?,
@@ -47,9 +62,54 @@ impl Package {
     self.manifest.agent()
   }
 
-  pub fn bump(self, release: &Release) -> Result<()> {
-    let version = self.version.with_release(release);
-    self.manifest.bump(&self, version)
+  /// Directory of the workspace this package belongs to, if any.
+  pub fn workspace(&self) -> Option<&Path> {
+    self.manifest.workspace_dir()
+  }
+
+  /// Directory where the package manager should run for this package.
+  pub fn root_dir(&self) -> &Path {
+    self
+      .workspace()
+      .or_else(|| self.path.parent())
+      .unwrap_or(&self.path)
+  }
+
+  /// Where the version of the package is actually stored.
+  pub fn version_source(&self) -> VersionSource {
+    match self.manifest.workspace_root() {
+      Some(root) => VersionSource::Workspace(root.to_path_buf()),
+      None => VersionSource::Manifest(self.path.clone()),
+    }
+  }
+
+  /// Version this package is bumped to by `release`.
+  pub fn next_version(&self, release: &Release) -> Version {
+    self.manifest.next_version(&self.version, release)
+  }
+
+  pub fn bump(&self, release: &Release) -> Result<()> {
+    let version = self.next_version(release);
+    self.manifest.bump(self, version)
+  }
+
+  /// Whether `bump` applies to this package, which needs a version unless it has not
+  /// been released yet.
+  pub fn is_bumpable(&self) -> bool {
+    self.version != DEFAULT_VERSION || self.manifest.is_unreleased()
+  }
+
+  /// Fails if `release` bumps this package to a version it cannot be bumped to.
+  pub fn check_bump(&self, release: &Release) -> Result<()> {
+    self
+      .manifest
+      .check_bump(&self.next_version(release))
+  }
+
+  /// Git tag to create for the bumped version, if the manifest cannot record it.
+  pub fn tag(&self, release: &Release) -> Option<String> {
+    let version = self.next_version(release);
+    self.manifest.tag(&version)
   }
 
   pub fn update(&self, tree: &DependencyTree, release: Option<&Release>) -> Result<()> {
@@ -98,6 +158,12 @@ impl Ord for Package {
   }
 }
 
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum VersionSource {
+  Manifest(PathBuf),
+  Workspace(PathBuf),
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
 pub struct GlobalPackage {
   pub agent: Agent,
@@ -107,16 +173,54 @@ pub struct GlobalPackage {
 impl GlobalPackage {
   // This will eventually be updated to also support Cargo.
   pub async fn get() -> Result<Vec<Self>> {
-    let mut packages = Vec::with_capacity(1);
+    let mut packages = Vec::with_capacity(2);
 
     packages.push(GlobalPackage {
       agent: Agent::Npm,
       dependencies: Self::node_dependencies().await?,
     });
 
+    if let Some(dependencies) = Self::bun_dependencies().await? {
+      packages.push(GlobalPackage { agent: Agent::Bun, dependencies });
+    }
+
     Ok(packages)
   }
 
+  /// Returns `None` if Bun is not installed.
+  async fn bun_dependencies() -> Result<Option<Vec<(String, Version)>>> {
+    // Bun is a real executable, so it is run directly: through `cmd /C`,
+    // a missing one would be just another failure instead of `NotFound`.
+    let output = match tokio::process::Command::new("bun")
+      .args(["pm", "ls", "--global"])
+      .output()
+      .await
+    {
+      Ok(output) => output,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    if !output.status.success() {
+      let stderr = String::from_utf8_lossy(&output.stderr);
+      bail!(stderr.into_owned());
+    }
+
+    // Each package is listed as a tree entry, e.g. `├── typescript@5.3.3`.
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let dependencies = stdout
+      .lines()
+      .filter_map(|line| {
+        let entry = line.trim_start_matches(['├', '└', '│', '─', ' ']);
+        let (name, version) = entry.rsplit_once('@')?;
+        let version = Version::parse(version.trim()).ok()?;
+        (!name.is_empty()).then(|| (name.to_owned(), version))
+      })
+      .collect_vec();
+
+    Ok(Some(dependencies))
+  }
+
   async fn node_dependencies() -> Result<Vec<(String, Version)>> {
     let output = command!("npm")
       .args(["list", "--global", "--json"])
@@ -158,10 +262,15 @@ impl GlobalPackage {
       .filter_map(|it| it.as_target(release))
       .collect_vec();
 
+    let (program, install) = match self.agent {
+      Agent::Bun => ("bun", "add"),
+      _ => ("npm", "install"),
+    };
+
     for target in targets {
       let arg = format!("{}@{}", target.dependency.name, target.comparator);
-      command!("npm")
-        .args(["install", &arg, "--global"])
+      command!(program)
+        .args([install, &arg, "--global"])
         .spawn()?
         .wait()
         .await?;
@@ -199,8 +308,27 @@ pub trait PackageDependencyTree {
 }
 
 impl PackageDependencyTree for Package {
+  /// Dependencies from the npm registry are fetched from the one configured in `.npmrc`, if any.
   fn dependency_tree(&self) -> DependencyTree {
-    self.manifest.dependency_tree()
+    let mut tree = self.manifest.dependency_tree();
+    let agent = tree.agent;
+    let mut npm = tree
+      .dependencies
+      .iter_mut()
+      .filter(|it| it.registry.unwrap_or(agent).is_node())
+      .peekable();
+
+    if npm.peek().is_some()
+      && let Ok(npmrc) = Npmrc::read(self.root_dir())
+    {
+      for dependency in npm {
+        let registry = npmrc.registry(dependency.registry_name());
+        dependency.registry_token = npmrc.auth(&registry);
+        dependency.registry_url = Some(registry);
+      }
+    }
+
+    tree
   }
 }
 
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use itertools::Itertools;
+use semver::{Comparator, Version};
+use std::path::Path;
+
+use super::Package;
+use crate::agent::Agent;
+use crate::dependency::{Dependency, Target};
+use crate::version::{ComparatorExt, VersionExt};
+
+/// A requirement on a package from the same repository that must follow its new version.
+#[derive(Debug)]
+pub struct DependentEdit<'a> {
+  pub dependent: &'a Package,
+  pub dependency: Dependency,
+  pub comparator: Comparator,
+}
+
+impl<'a> DependentEdit<'a> {
+  /// Finds the requirements on the `bumped` packages among `packages`.
+  pub fn find(bumped: &[(&Package, Version)], packages: &'a [Package]) -> Vec<Self> {
+    let mut edits = Vec::new();
+
+    for dependent in packages {
+      let tree = dependent.manifest.internal_dependency_tree();
+      for dependency in tree.dependencies {
+        let Some((_, version)) = bumped.iter().find(|(package, _)| {
+          package.name == dependency.registry_name()
+            && package.path != dependent.path
+            && same_ecosystem(package.agent(), dependent.agent())
+        }) else {
+          continue;
+        };
+
+        let mut comparator = version.as_comparator(dependency.comparator.op);
+        if comparator.pre.is_empty() {
+          dependency.comparator.normalize(&mut comparator);
+        }
+
+        if comparator != dependency.comparator {
+          edits.push(Self { dependent, dependency, comparator });
+        }
+      }
+    }
+
+    edits
+  }
+
+  pub fn apply(edits: &[Self]) -> Result<()> {
+    let groups = edits
+      .iter()
+      .into_group_map_by(|it| &it.dependent.path);
+
+    for edits in groups.into_values() {
+      let dependent = edits[0].dependent;
+      let targets = edits
+        .iter()
+        .map(|it| Target::new(&it.dependency, it.comparator.clone()))
+        .collect_vec();
+
+      dependent.manifest.update(dependent, &targets)?;
+    }
+
+    Ok(())
+  }
+}
+
+fn same_ecosystem(a: Agent, b: Agent) -> bool {
+  a == b || (a.is_node() && b.is_node())
+}
+
+/// A package that copies the new version of another one, such as the `appVersion` of a Helm chart.
+#[derive(Debug)]
+pub struct FollowerEdit<'a> {
+  pub follower: &'a Package,
+  pub leader: String,
+  pub version: Version,
+}
+
+impl<'a> FollowerEdit<'a> {
+  /// Finds the packages among `packages` that follow one of the `bumped` packages.
+  pub fn find(bumped: &[(&Package, Version)], packages: &'a [Package]) -> Vec<Self> {
+    let mut edits = Vec::new();
+
+    for follower in packages {
+      let Some(leader) = follower.manifest.leader() else {
+        continue;
+      };
+
+      if let Some((package, version)) = bumped
+        .iter()
+        .find(|(package, _)| same_file(&package.path, leader))
+      {
+        edits.push(Self {
+          follower,
+          leader: package.name.clone(),
+          version: version.clone(),
+        });
+      }
+    }
+
+    edits
+  }
+
+  pub fn apply(edits: &[Self]) -> Result<()> {
+    for edit in edits {
+      edit
+        .follower
+        .manifest
+        .follow(edit.follower, &edit.version)?;
+    }
+
+    Ok(())
+  }
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+  match (a.canonicalize(), b.canonicalize()) {
+    (Ok(a), Ok(b)) => a == b,
+    _ => a == b,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+
+  fn package_json(name: &str, dependencies: &str) -> String {
+    format!(r#"{{ "name": "{name}", "version": "1.0.0", "dependencies": {{ {dependencies} }} }}"#)
+  }
+
+  fn cargo_toml(name: &str, dependencies: &str) -> String {
+    format!("[package]\nname = \"{name}\"\nversion = \"1.0.0\"\n\n[dependencies]\n{dependencies}\n")
+  }
+
+  #[test]
+  fn propagates_versions_to_dependents() {
+    let dir = TempDir::new();
+    let paths = [
+      dir.write("a/package.json", &package_json("a", "")),
+      dir.write(
+        "b/package.json",
+        &package_json("b", r#""a": "workspace:^1.0.0""#),
+      ),
+      dir.write(
+        "c/Cargo.toml",
+        &cargo_toml("c", r#"d = { path = "../d", version = "1.0.0" }"#),
+      ),
+      dir.write("d/Cargo.toml", &cargo_toml("d", "")),
+    ];
+
+    let packages = paths
+      .iter()
+      .map(|it| Package::new(it).unwrap())
+      .collect_vec();
+    let bumped = [
+      (&packages[0], Version::new(1, 1, 0)),
+      (&packages[3], Version::new(2, 0, 0)),
+    ];
+
+    let edits = DependentEdit::find(&bumped, &packages);
+    assert_eq!(edits.len(), 2);
+    DependentEdit::apply(&edits).unwrap();
+
+    assert_eq!(
+      dir.read("b/package.json"),
+      package_json("b", r#""a": "workspace:^1.1.0""#)
+    );
+    assert_eq!(
+      dir.read("c/Cargo.toml"),
+      cargo_toml("c", r#"d = { path = "../d", version = "2.0.0" }"#)
+    );
+  }
+
+  #[test]
+  fn leaves_matching_requirements_untouched() {
+    let dir = TempDir::new();
+    let paths = [
+      dir.write("a/package.json", &package_json("a", "")),
+      dir.write(
+        "b/package.json",
+        &package_json("b", r#""a": "workspace:*""#),
+      ),
+      dir.write(
+        "c/package.json",
+        &package_json("c", r#""a": "workspace:^""#),
+      ),
+      dir.write(
+        "d/package.json",
+        &package_json("d", r#""a": "workspace:~1.0""#),
+      ),
+      dir.write("e/package.json", &package_json("e", r#""a": "^1.0.0""#)),
+      // Same name, but another ecosystem.
+      dir.write(
+        "f/Cargo.toml",
+        &cargo_toml("f", r#"a = { path = "../g", version = "=1.0.0" }"#),
+      ),
+      dir.write("g/Cargo.toml", &cargo_toml("g", "")),
+    ];
+
+    let packages = paths
+      .iter()
+      .map(|it| Package::new(it).unwrap())
+      .collect_vec();
+    let bumped = [(&packages[0], Version::new(1, 0, 1))];
+
+    let edits = DependentEdit::find(&bumped, &packages);
+    assert!(edits.is_empty(), "{edits:?}");
+  }
+
+  #[test]
+  fn finds_followers() {
+    let dir = TempDir::new();
+    let leader = dir.write("app/package.json", &package_json("app", ""));
+    let other = dir.write("other/package.json", &package_json("other", ""));
+    dir.write(
+      "app/miho.toml",
+      "[web-extension]\nfollow-package-json = true\n",
+    );
+    let extension = dir.write(
+      "app/manifest.json",
+      r#"{ "manifest_version": 3, "name": "app", "version": "1.0.0" }"#,
+    );
+
+    let packages = [&leader, &other, &extension].map(|it| Package::new(it).unwrap());
+
+    let bumped = [(&packages[1], Version::new(2, 0, 0))];
+    assert!(FollowerEdit::find(&bumped, &packages).is_empty());
+
+    let bumped = [(&packages[0], Version::new(1, 2, 0))];
+    let edits = FollowerEdit::find(&bumped, &packages);
+    let [edit] = edits.as_slice() else {
+      panic!("expected one follower");
+    };
+
+    assert_eq!(edit.leader, "app");
+    assert_eq!(edit.follower.path, extension);
+    FollowerEdit::apply(&edits).unwrap();
+
+    let manifest = dir.read("app/manifest.json");
+    assert!(manifest.contains(r#""version": "1.2.0""#), "{manifest}");
+    assert!(
+      manifest.contains(r#""version_name": "1.2.0""#),
+      "{manifest}"
+    );
+  }
+}
@@ -1,20 +1,53 @@
+mod cargo_config;
 mod cargo_toml;
+mod chart_yaml;
+mod composer_json;
+mod csproj;
+mod deno_json;
+mod go_mod;
+mod gradle_properties;
+mod json;
+mod manifest_json;
 mod package_json;
+mod pnpm_workspace_yaml;
+mod pom_xml;
+mod pubspec_yaml;
+mod pyproject_toml;
 mod tauri_conf_json;
+mod version_file;
+mod xml;
+mod yaml;
 
 use anyhow::{Result, anyhow};
 use cargo_toml::CargoToml;
+use chart_yaml::ChartYaml;
+use composer_json::ComposerJson;
+use csproj::Csproj;
+use deno_json::DenoJson;
 use dependency::DependencyTree;
 use globset::Glob;
+use go_mod::GoMod;
+use gradle_properties::GradleProperties;
+use manifest_json::ManifestJson;
 use package_json::PackageJson;
-use semver::Version;
+use pnpm_workspace_yaml::PnpmWorkspaceYaml;
+use pom_xml::PomXml;
+use pubspec_yaml::PubspecYaml;
+use pyproject_toml::PyprojectToml;
+use semver::{Comparator, Version};
 use std::path::Path;
 use strum::{EnumIter, IntoEnumIterator};
 use tauri_conf_json::TauriConfJson;
 
+pub use pyproject_toml::normalize_name as normalize_python_name;
+pub use version_file::VersionFile;
+pub use xml::XmlDocument;
+
 use super::Package;
 use crate::agent::Agent;
 use crate::dependency::{self, Target};
+use crate::release::Release;
+use crate::version::VersionExt;
 
 pub(super) type ManifestBox = Box<dyn Handler + Send + Sync>;
 
@@ -39,12 +72,71 @@ pub trait Handler {
   fn dependency_tree(&self) -> DependencyTree {
     DependencyTree::new(self.agent())
   }
+
+  /// Dependencies on other packages of the same repository,
+  /// such as Cargo path dependencies or `workspace:` specs.
+  fn internal_dependency_tree(&self) -> DependencyTree {
+    DependencyTree::new(self.agent())
+  }
+
+  /// Directory of the workspace this package belongs to, if any.
+  fn workspace_dir(&self) -> Option<&Path> {
+    None
+  }
+
+  /// Manifest of the package whose version this one follows, if any.
+  fn leader(&self) -> Option<&Path> {
+    None
+  }
+
+  /// Copies the new version of the package returned by [`Handler::leader`].
+  fn follow(&self, _package: &Package, _version: &Version) -> Result<()> {
+    Ok(())
+  }
+
+  /// Version that `release` bumps the current `version` to.
+  fn next_version(&self, version: &Version, release: &Release) -> Version {
+    version.with_release(release)
+  }
+
+  /// Git tag recording `version`, for packages whose manifest has no version field.
+  fn tag(&self, _version: &Version) -> Option<String> {
+    None
+  }
+
+  /// Fails if the package cannot be bumped to `version` by editing its manifest.
+  fn check_bump(&self, _version: &Version) -> Result<()> {
+    Ok(())
+  }
+
+  /// Whether the package has no version yet but can still be bumped from [`DEFAULT_VERSION`],
+  /// such as a Go module that was never tagged.
+  fn is_unreleased(&self) -> bool {
+    false
+  }
+
+  /// Manifest of the workspace root, if the version is inherited from it.
+  fn workspace_root(&self) -> Option<&Path> {
+    None
+  }
 }
 
 #[derive(Debug, EnumIter)]
 pub enum ManifestKind {
   CargoToml,
+  ChartYaml,
+  ComposerJson,
+  Csproj,
+  DenoJson,
+  DirectoryBuildProps,
+  GoMod,
+  GradleProperties,
+  ManifestJson,
   PackageJson,
+  PnpmWorkspaceYaml,
+  PomXml,
+  PubspecYaml,
+  PyprojectToml,
   TauriConfJson,
 }
 
@@ -52,7 +144,18 @@ impl ManifestKind {
   pub(crate) fn read<P: AsRef<Path>>(&self, path: P) -> Result<ManifestBox> {
     match self {
       ManifestKind::CargoToml => CargoToml::read(path),
+      ManifestKind::ChartYaml => ChartYaml::read(path),
+      ManifestKind::ComposerJson => ComposerJson::read(path),
+      ManifestKind::Csproj | ManifestKind::DirectoryBuildProps => Csproj::read(path),
+      ManifestKind::DenoJson => DenoJson::read(path),
+      ManifestKind::GoMod => GoMod::read(path),
+      ManifestKind::GradleProperties => GradleProperties::read(path),
+      ManifestKind::ManifestJson => ManifestJson::read(path),
       ManifestKind::PackageJson => PackageJson::read(path),
+      ManifestKind::PnpmWorkspaceYaml => PnpmWorkspaceYaml::read(path),
+      ManifestKind::PomXml => PomXml::read(path),
+      ManifestKind::PubspecYaml => PubspecYaml::read(path),
+      ManifestKind::PyprojectToml => PyprojectToml::read(path),
       ManifestKind::TauriConfJson => TauriConfJson::read(path),
     }
   }
@@ -60,7 +163,19 @@ impl ManifestKind {
   pub(crate) fn glob(&self) -> &str {
     match self {
       ManifestKind::CargoToml => "**/Cargo.toml",
+      ManifestKind::ChartYaml => "**/Chart.yaml",
+      ManifestKind::ComposerJson => "**/composer.json",
+      ManifestKind::Csproj => "**/*.csproj",
+      ManifestKind::DenoJson => "**/{deno,jsr}.json",
+      ManifestKind::DirectoryBuildProps => "**/Directory.Build.props",
+      ManifestKind::GoMod => "**/go.mod",
+      ManifestKind::GradleProperties => "**/gradle.properties",
+      ManifestKind::ManifestJson => "**/manifest.json",
       ManifestKind::PackageJson => "**/package.json",
+      ManifestKind::PnpmWorkspaceYaml => "**/pnpm-workspace.yaml",
+      ManifestKind::PomXml => "**/pom.xml",
+      ManifestKind::PubspecYaml => "**/pubspec.yaml",
+      ManifestKind::PyprojectToml => "**/pyproject.toml",
       ManifestKind::TauriConfJson => "**/tauri.conf.json",
     }
   }
@@ -85,3 +200,48 @@ impl TryFrom<&Path> for ManifestKind {
 fn default_version() -> String {
   DEFAULT_VERSION.to_string()
 }
+
+/// Used as the name of manifests that have none, such as virtual workspaces.
+fn dir_name(path: &Path) -> String {
+  path
+    .parent()
+    .and_then(Path::file_name)
+    .map(|it| it.to_string_lossy().into_owned())
+    .unwrap_or_else(|| String::from("workspace"))
+}
+
+/// Version of a comparator without its operator, e.g. `2.31` for `^2.31`.
+fn version_text(comparator: &Comparator) -> String {
+  comparator
+    .to_string()
+    .trim_start_matches(['=', '>', '<', '^', '~'])
+    .to_owned()
+}
+
+/// Parses a constraint in which a bare version is an exact pin, as in Poetry and pub.
+fn parse_pinned_constraint(constraint: &str) -> Option<Comparator> {
+  let constraint = constraint.trim();
+  if constraint.starts_with(|it: char| it.is_ascii_digit()) {
+    Comparator::parse(&format!("={constraint}")).ok()
+  } else {
+    Comparator::parse(constraint).ok()
+  }
+}
+
+/// Replaces the version of `constraint` with the one of `comparator`, keeping the original operator.
+fn replace_version(constraint: &str, comparator: &Comparator) -> String {
+  let start = constraint
+    .find(|it: char| it.is_ascii_digit())
+    .unwrap_or(constraint.len());
+
+  format!("{}{}", &constraint[..start], version_text(comparator))
+}
+
+/// Replaces the TOML string in place, keeping the surrounding whitespace and comments.
+fn set_toml_string(item: &mut toml_edit::Item, value: &str) {
+  if let Some(old) = item.as_value_mut() {
+    let decor = old.decor().clone();
+    *old = toml_edit::Value::from(value);
+    *old.decor_mut() = decor;
+  }
+}
@@ -0,0 +1,324 @@
+use anyhow::{Result, anyhow, bail};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::registry::Token;
+
+const CRATES_IO: &str = "crates-io";
+
+/// Registries and source replacements from Cargo's config files and environment.
+///
+/// <https://doc.rust-lang.org/cargo/reference/config.html>
+#[derive(Default)]
+pub(super) struct CargoConfig {
+  registries: HashMap<String, RegistryConfig>,
+  sources: HashMap<String, SourceConfig>,
+}
+
+/// Index a dependency is fetched from, when it is not the crates.io one.
+pub(super) struct RegistryIndex {
+  pub url: String,
+  pub token: Option<Token>,
+}
+
+#[derive(Deserialize)]
+struct ConfigFile {
+  #[serde(default)]
+  registries: HashMap<String, RegistryConfig>,
+  #[serde(default)]
+  source: HashMap<String, SourceConfig>,
+}
+
+#[derive(Default, Deserialize)]
+struct RegistryConfig {
+  index: Option<String>,
+  token: Option<String>,
+}
+
+/// <https://doc.rust-lang.org/cargo/reference/source-replacement.html>
+#[derive(Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SourceConfig {
+  replace_with: Option<String>,
+  registry: Option<String>,
+  directory: Option<String>,
+  local_registry: Option<String>,
+  git: Option<String>,
+}
+
+impl CargoConfig {
+  /// Reads the config files Cargo would use when run at `dir`, the closest one taking precedence,
+  /// followed by the credentials at `$CARGO_HOME`.
+  pub fn read(dir: &Path) -> Result<Self> {
+    let mut config = Self::default();
+    let home = cargo_home();
+
+    let mut dirs = dir
+      .ancestors()
+      .map(|it| it.join(".cargo"))
+      .collect::<Vec<_>>();
+
+    if let Some(home) = &home
+      && !dirs.contains(home)
+    {
+      dirs.push(home.clone());
+    }
+
+    for dir in dirs.iter().rev() {
+      if let Some(file) = read_file(dir, "config")? {
+        config.merge(file);
+      }
+    }
+
+    if let Some(home) = &home
+      && let Some(file) = read_file(home, "credentials")?
+    {
+      config.merge(file);
+    }
+
+    Ok(config)
+  }
+
+  fn merge(&mut self, file: ConfigFile) {
+    for (name, registry) in file.registries {
+      let entry = self.registries.entry(name).or_default();
+      entry.index = registry.index.or(entry.index.take());
+      entry.token = registry.token.or(entry.token.take());
+    }
+
+    for (name, source) in file.source {
+      let entry = self.sources.entry(name).or_default();
+      entry.replace_with = source.replace_with.or(entry.replace_with.take());
+      entry.registry = source.registry.or(entry.registry.take());
+      entry.directory = source.directory.or(entry.directory.take());
+      entry.local_registry = source
+        .local_registry
+        .or(entry.local_registry.take());
+      entry.git = source.git.or(entry.git.take());
+    }
+  }
+
+  /// Index of the named `registry`, or of crates.io if `None`, after following source replacements.
+  /// Returns `None` for crates.io itself.
+  ///
+  /// Vendored and git sources have no index to read versions from, so the registry they
+  /// replace is used instead.
+  pub fn resolve(&self, registry: Option<&str>) -> Result<Option<RegistryIndex>> {
+    let original = registry.unwrap_or(CRATES_IO);
+    let mut name = original.to_owned();
+    let mut visited = HashSet::new();
+
+    while let Some(replacement) = self
+      .sources
+      .get(&name)
+      .and_then(|it| it.replace_with.as_ref())
+    {
+      if !visited.insert(name.clone()) {
+        bail!("source replacement of {name} is circular");
+      }
+
+      name.clone_from(replacement);
+    }
+
+    if self
+      .sources
+      .get(&name)
+      .is_some_and(SourceConfig::is_vendored)
+    {
+      original.clone_into(&mut name);
+    }
+
+    if let Some(url) = self
+      .sources
+      .get(&name)
+      .and_then(|it| it.registry.as_deref())
+    {
+      return Ok(Some(RegistryIndex {
+        url: sparse_url(&name, url)?,
+        token: None,
+      }));
+    }
+
+    if name == CRATES_IO {
+      return Ok(None);
+    }
+
+    let Some(url) = self.index(&name) else {
+      bail!("no index found for registry {name}");
+    };
+
+    let index = RegistryIndex {
+      url: sparse_url(&name, &url)?,
+      token: self.token(&name).map(Token::new),
+    };
+
+    Ok(Some(index))
+  }
+
+  fn index(&self, name: &str) -> Option<String> {
+    env::var(registry_var(name, "INDEX"))
+      .ok()
+      .or_else(|| {
+        self
+          .registries
+          .get(name)
+          .and_then(|it| it.index.clone())
+      })
+  }
+
+  fn token(&self, name: &str) -> Option<String> {
+    env::var(registry_var(name, "TOKEN"))
+      .ok()
+      .or_else(|| {
+        self
+          .registries
+          .get(name)
+          .and_then(|it| it.token.clone())
+      })
+  }
+}
+
+impl SourceConfig {
+  fn is_vendored(&self) -> bool {
+    self.directory.is_some() || self.local_registry.is_some() || self.git.is_some()
+  }
+}
+
+/// `$CARGO_HOME`, which defaults to `~/.cargo`.
+fn cargo_home() -> Option<PathBuf> {
+  env::var_os("CARGO_HOME")
+    .map(PathBuf::from)
+    .or_else(|| env::home_dir().map(|it| it.join(".cargo")))
+}
+
+/// Reads `name.toml` at `dir`, or `name` without the extension, as older versions of Cargo did.
+fn read_file(dir: &Path, name: &str) -> Result<Option<ConfigFile>> {
+  for path in [dir.join(format!("{name}.toml")), dir.join(name)] {
+    if path.is_file() {
+      let contents = fs::read_to_string(&path)?;
+      let file = toml::from_str(&contents).map_err(|err| anyhow!("{}: {err}", path.display()))?;
+      return Ok(Some(file));
+    }
+  }
+
+  Ok(None)
+}
+
+/// Environment variable of a registry, such as `CARGO_REGISTRIES_MY_REGISTRY_INDEX`.
+fn registry_var(name: &str, key: &str) -> String {
+  let name = name.to_uppercase().replace('-', "_");
+  format!("CARGO_REGISTRIES_{name}_{key}")
+}
+
+/// Only sparse indexes are supported, as git ones would have to be cloned.
+fn sparse_url(name: &str, url: &str) -> Result<String> {
+  let Some(url) = url.strip_prefix("sparse+") else {
+    bail!("registry {name} has no sparse index: {url}");
+  };
+
+  Ok(url.trim_end_matches('/').to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn config(contents: &str) -> CargoConfig {
+    let mut config = CargoConfig::default();
+    config.merge(toml::from_str(contents).unwrap());
+    config
+  }
+
+  fn url(config: &CargoConfig, registry: Option<&str>) -> Option<String> {
+    config.resolve(registry).unwrap().map(|it| it.url)
+  }
+
+  #[test]
+  fn resolves_registries() {
+    let config = config(
+      r#"
+[registries.private]
+index = "sparse+https://private.example.com/index/"
+token = "secret"
+
+[registries.git]
+index = "https://example.com/git-index"
+"#,
+    );
+
+    assert_eq!(url(&config, None), None);
+
+    let private = config.resolve(Some("private")).unwrap().unwrap();
+    assert_eq!(private.url, "https://private.example.com/index");
+    assert!(private.token.is_some());
+
+    assert!(config.resolve(Some("git")).is_err());
+    assert!(config.resolve(Some("unknown")).is_err());
+  }
+
+  #[test]
+  fn follows_source_replacements() {
+    let config = config(
+      r#"
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+replace-with = "sparse-mirror"
+
+[source.sparse-mirror]
+registry = "sparse+https://mirror.example.com/"
+"#,
+    );
+
+    assert_eq!(
+      url(&config, None).as_deref(),
+      Some("https://mirror.example.com")
+    );
+  }
+
+  #[test]
+  fn falls_back_to_the_registry_of_vendored_sources() {
+    let config = config(
+      r#"
+[registries.private]
+index = "sparse+https://private.example.com/index"
+
+[source.crates-io]
+replace-with = "vendored-sources"
+
+[source.private]
+replace-with = "local"
+
+[source.vendored-sources]
+directory = "vendor"
+
+[source.local]
+local-registry = "registry"
+"#,
+    );
+
+    assert_eq!(url(&config, None), None);
+    assert_eq!(
+      url(&config, Some("private")).as_deref(),
+      Some("https://private.example.com/index")
+    );
+  }
+
+  #[test]
+  fn rejects_circular_replacements() {
+    let config = config(
+      r#"
+[source.crates-io]
+replace-with = "mirror"
+
+[source.mirror]
+replace-with = "crates-io"
+"#,
+    );
+
+    assert!(config.resolve(None).is_err());
+  }
+}
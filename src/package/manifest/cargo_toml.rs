@@ -1,21 +1,61 @@
-use anyhow::Result;
-use semver::Version;
+use anyhow::{Result, bail};
+use colored::Colorize;
+use globset::{GlobBuilder, GlobSetBuilder};
+use itertools::Itertools;
+use semver::{Comparator, Version};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
-use taplo::formatter;
+use std::path::{Component, Path, PathBuf};
 use toml::Value;
+use toml_edit::{DocumentMut, Item};
 
-use super::default_version;
+use super::cargo_config::CargoConfig;
+use super::{DEFAULT_VERSION, dir_name, set_toml_string};
 use crate::agent::Agent;
-use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::dependency::{self, Dependency, DependencyKind, DependencyTree};
 use crate::package::Package;
 use crate::package::manifest::{Handler, Manifest, ManifestBox};
+use crate::version::parse_partial;
 
 #[derive(Deserialize)]
 pub(super) struct CargoToml {
-  pub package: CargoPackage,
+  pub package: Option<CargoPackage>,
+  pub workspace: Option<CargoWorkspace>,
+
+  #[serde(flatten)]
+  pub dependencies: DependencyTables,
+
+  /// <https://doc.rust-lang.org/cargo/reference/specifying-dependencies.html#platform-specific-dependencies>
+  pub target: Option<HashMap<String, DependencyTables>>,
+
+  /// Name of the package, or of the directory when this is a virtual workspace.
+  #[serde(skip)]
+  name: String,
+
+  #[serde(skip)]
+  path: PathBuf,
+
+  /// Workspace this manifest belongs to, if any.
+  #[serde(skip)]
+  root: Option<WorkspaceRoot>,
+
+  /// Cargo config at the directory of the manifest, used to find alternative registries.
+  #[serde(skip)]
+  config: CargoConfig,
+}
+
+#[derive(Deserialize)]
+pub(super) struct CargoPackage {
+  pub name: String,
+  pub version: Option<MaybeWorkspace>,
+  pub workspace: Option<PathBuf>,
+  #[serde(rename(deserialize = "rust-version"))]
+  pub rust_version: Option<MaybeWorkspace>,
+}
+
+#[derive(Default, Deserialize)]
+pub(super) struct DependencyTables {
   pub dependencies: Option<HashMap<String, Value>>,
 
   #[serde(rename(deserialize = "dev-dependencies"))]
@@ -25,27 +65,97 @@ pub(super) struct CargoToml {
   pub build_dependencies: Option<HashMap<String, Value>>,
 }
 
+/// <https://doc.rust-lang.org/cargo/reference/workspaces.html>
 #[derive(Deserialize)]
-pub(super) struct CargoPackage {
-  pub name: String,
-  #[serde(default = "default_version")]
-  pub version: String,
+pub(super) struct CargoWorkspace {
+  pub package: Option<WorkspacePackage>,
+  pub dependencies: Option<HashMap<String, Value>>,
+  #[serde(default)]
+  pub members: Vec<String>,
+  #[serde(default)]
+  pub exclude: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct WorkspacePackage {
+  pub version: Option<String>,
+  #[serde(rename(deserialize = "rust-version"))]
+  pub rust_version: Option<String>,
+}
+
+/// A field that may be inherited with `field.workspace = true`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub(super) enum MaybeWorkspace {
+  Value(String),
+  Workspace { workspace: bool },
+}
+
+/// Only used to check whether a `Cargo.toml` is the root of a workspace, and of which packages.
+#[derive(Deserialize)]
+struct WorkspaceManifest {
+  workspace: Option<CargoWorkspace>,
+  #[serde(flatten)]
+  dependencies: DependencyTables,
+}
+
+/// A dependency as declared in the manifest, after resolving workspace inheritance.
+struct ParsedDependency<'a> {
+  name: &'a str,
+  /// Real package name, if renamed.
+  package: Option<&'a str>,
+  /// Name of an alternative registry, as configured in `.cargo/config.toml`.
+  registry: Option<&'a str>,
+  version: &'a str,
+  features: Vec<String>,
+}
+
+struct WorkspaceRoot {
+  path: PathBuf,
+  /// Globs of the member directories, relative to the root.
+  members: Vec<String>,
+  exclude: Vec<String>,
+  /// Directories of the path dependencies of the root package, which are members as well.
+  path_dependencies: Vec<PathBuf>,
+  version: Option<String>,
+  rust_version: Option<String>,
+  dependencies: HashMap<String, Value>,
 }
 
 impl Manifest for CargoToml {
-  type Value = toml::Value;
+  type Value = DocumentMut;
 
   const FILENAME: &'static str = "Cargo.toml";
 
   fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
     let contents = fs::read_to_string(path)?;
-    let manifest: CargoToml = toml::from_str(&contents)?;
+    let mut manifest: CargoToml = toml::from_str(&contents)?;
+
+    manifest.name = match (&manifest.package, &manifest.workspace) {
+      (Some(package), _) => package.name.clone(),
+      (None, Some(_)) => dir_name(path),
+      (None, None) => bail!("{} has neither [package] nor [workspace]", path.display()),
+    };
+
+    manifest.path = path.to_path_buf();
+    manifest.config = CargoConfig::read(path.parent().unwrap_or(path)).unwrap_or_else(|err| {
+      // Dependencies are then looked up at crates.io, which is still right for most of them.
+      let warning = "ignored Cargo config:".bright_yellow();
+      println!("{warning} {err}");
+      CargoConfig::default()
+    });
+    manifest.root = match manifest.workspace.take() {
+      Some(workspace) => Some(WorkspaceRoot::new(path, workspace, &manifest.dependencies)),
+      None => manifest.find_workspace(path),
+    };
+
     Ok(Box::new(manifest))
   }
 
   fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
     let contents = fs::read_to_string(path)?;
-    let manifest: Self::Value = toml::from_str(&contents)?;
+    let manifest: Self::Value = contents.parse()?;
     Ok(manifest)
   }
 }
@@ -56,11 +166,30 @@ impl Handler for CargoToml {
   }
 
   fn bump(&self, package: &Package, version: Version) -> Result<()> {
-    let mut manifest = CargoToml::read_as_value(&package.path)?;
-    manifest["package"]["version"] = Value::String(version.to_string());
+    let version = version.to_string();
+
+    if let Some(root) = self.workspace_root() {
+      let mut manifest = CargoToml::read_as_value(root)?;
+      if let Some(item) = manifest
+        .get_mut("workspace")
+        .and_then(|it| it.get_mut("package"))
+        .and_then(|it| it.get_mut("version"))
+      {
+        set_toml_string(item, &version);
+      }
 
-    let contents = format(&manifest)?;
-    fs::write(&package.path, contents)?;
+      fs::write(root, manifest.to_string())?;
+    } else {
+      let mut manifest = CargoToml::read_as_value(&package.path)?;
+      if let Some(item) = manifest
+        .get_mut("package")
+        .and_then(|it| it.get_mut("version"))
+      {
+        set_toml_string(item, &version);
+      }
+
+      fs::write(&package.path, manifest.to_string())?;
+    }
 
     Ok(())
   }
@@ -68,87 +197,443 @@ impl Handler for CargoToml {
   fn dependency_tree(&self) -> DependencyTree {
     let mut tree = DependencyTree::new(self.agent());
 
-    macro_rules! add {
-      ($dependencies:expr, $kind:ident) => {
-        if let Some(deps) = $dependencies {
-          let dependencies = parse_dependencies(deps);
-          tree.add_many(&dependencies, DependencyKind::$kind);
+    for kind in DependencyTables::KINDS {
+      if let Some(deps) = self.dependencies.get(kind) {
+        self.add_dependencies(&mut tree, deps, kind, None);
+      }
+
+      for (cfg, tables) in self.target.iter().flatten() {
+        if let Some(deps) = tables.get(kind) {
+          self.add_dependencies(&mut tree, deps, kind, Some(cfg));
+        }
+      }
+    }
+
+    if let Some(root) = &self.root
+      && root.path == self.path
+    {
+      let kind = DependencyKind::Workspace;
+      self.add_dependencies(&mut tree, &root.dependencies, kind, None);
+    }
+
+    tree
+  }
+
+  fn internal_dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    let mut add = |deps: &HashMap<String, Value>, kind: DependencyKind, cfg: Option<&str>| {
+      for (name, value) in deps {
+        let path = value.get("path");
+        let version = value.get("version").and_then(Value::as_str);
+        if let (Some(_), Some(version)) = (path, version)
+          && let Ok(comparator) = Comparator::parse(version)
+        {
+          let package = value.get("package").and_then(Value::as_str);
+          let dependency = tree.add_renamed(name, package, comparator, kind);
+          dependency.target = cfg.map(ToOwned::to_owned);
         }
-      };
+      }
+    };
+
+    for kind in DependencyTables::KINDS {
+      if let Some(deps) = self.dependencies.get(kind) {
+        add(deps, kind, None);
+      }
+
+      for (cfg, tables) in self.target.iter().flatten() {
+        if let Some(deps) = tables.get(kind) {
+          add(deps, kind, Some(cfg));
+        }
+      }
     }
 
-    add!(&self.dependencies, Normal);
-    add!(&self.dev_dependencies, Development);
-    add!(&self.build_dependencies, Build);
+    if let Some(root) = &self.root
+      && root.path == self.path
+    {
+      add(&root.dependencies, DependencyKind::Workspace, None);
+    }
 
     tree
   }
 
   fn name(&self) -> &str {
-    self.package.name.as_str()
+    self.name.as_str()
   }
 
   fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
     let mut manifest = CargoToml::read_as_value(&package.path)?;
+    let mut root = match &self.root {
+      Some(root) if root.path != package.path => Some(CargoToml::read_as_value(&root.path)?),
+      _ => None,
+    };
 
     for target in targets {
-      let key = match target.dependency.kind {
+      let dependency = target.dependency;
+      let name = dependency.name.as_str();
+      let cfg = dependency.target.as_deref();
+      let key = match dependency.kind {
         DependencyKind::Normal => "dependencies",
         DependencyKind::Development => "dev-dependencies",
         DependencyKind::Build => "build-dependencies",
-        DependencyKind::Peer | DependencyKind::PackageManager => continue,
+        DependencyKind::Workspace => "workspace",
+        DependencyKind::Catalog
+        | DependencyKind::Import
+        | DependencyKind::Management
+        | DependencyKind::Parent
+        | DependencyKind::Peer
+        | DependencyKind::PackageManager
+        | DependencyKind::Resolution => continue,
       };
 
-      let version = manifest
-        .get_mut(key)
-        .and_then(Value::as_table_mut)
-        .and_then(|deps| deps.get_mut(&target.dependency.name));
+      let dependencies = if key == "workspace" || self.is_inherited(dependency, cfg) {
+        root
+          .as_mut()
+          .unwrap_or(&mut manifest)
+          .get_mut("workspace")
+          .and_then(|it| it.get_mut("dependencies"))
+      } else if let Some(cfg) = cfg {
+        manifest
+          .get_mut("target")
+          .and_then(|it| it.get_mut(cfg))
+          .and_then(|it| it.get_mut(key))
+      } else {
+        manifest.get_mut(key)
+      };
+
+      let dependency = dependencies
+        .and_then(Item::as_table_like_mut)
+        .and_then(|deps| deps.get_mut(name));
 
-      if let Some(value) = version {
+      if let Some(item) = dependency {
         let mut comparator = target.comparator.to_string();
         if comparator.starts_with('^') {
           comparator.remove(0);
         }
 
-        if value.is_str() {
-          *value = Value::String(comparator);
-        } else if value.is_table() {
-          value["version"] = Value::String(comparator);
+        if item.is_str() {
+          set_toml_string(item, &comparator);
+        } else if let Some(version) = item
+          .as_table_like_mut()
+          .and_then(|it| it.get_mut("version"))
+        {
+          set_toml_string(version, &comparator);
         }
       }
     }
 
-    let contents = format(&manifest)?;
-    fs::write(&package.path, contents)?;
+    fs::write(&package.path, manifest.to_string())?;
+
+    if let (Some(root), Some(manifest)) = (&self.root, root) {
+      fs::write(&root.path, manifest.to_string())?;
+    }
 
     Ok(())
   }
 
   fn version(&self) -> Result<Version> {
-    Version::parse(&self.package.version).map_err(Into::into)
+    let inherited = self
+      .root
+      .as_ref()
+      .and_then(|it| it.version.as_ref());
+    let version = match self.package.as_ref().map(|it| &it.version) {
+      Some(Some(MaybeWorkspace::Value(version))) => Some(version),
+      Some(Some(MaybeWorkspace::Workspace { .. })) if inherited.is_none() => {
+        bail!(
+          "{} inherits its version, but the workspace has none",
+          self.name
+        );
+      }
+      Some(Some(MaybeWorkspace::Workspace { .. })) | None => inherited,
+      Some(None) => None,
+    };
+
+    match version {
+      Some(version) => Version::parse(version).map_err(Into::into),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+
+  fn workspace_dir(&self) -> Option<&Path> {
+    self.root.as_ref()?.path.parent()
+  }
+
+  fn workspace_root(&self) -> Option<&Path> {
+    let root = self.root.as_ref()?;
+    let inherits = match self.package.as_ref() {
+      Some(package) => matches!(
+        package.version,
+        Some(MaybeWorkspace::Workspace { workspace: true })
+      ),
+      None => root.version.is_some(),
+    };
+
+    inherits.then_some(root.path.as_path())
+  }
+}
+
+impl CargoToml {
+  /// Looks for the workspace root among the ancestors of `path`, as Cargo does,
+  /// unless `package.workspace` points directly to it.
+  ///
+  /// <https://doc.rust-lang.org/cargo/reference/workspaces.html#the-members-and-exclude-fields>
+  fn find_workspace(&self, path: &Path) -> Option<WorkspaceRoot> {
+    let dir = path.parent()?;
+
+    if let Some(workspace) = self
+      .package
+      .as_ref()
+      .and_then(|it| it.workspace.as_deref())
+    {
+      let root = dir.join(workspace).join(Self::FILENAME);
+      return WorkspaceRoot::read_or_warn(&root);
+    }
+
+    for ancestor in dir.ancestors().skip(1) {
+      let root = ancestor.join(Self::FILENAME);
+      if !matches!(root.try_exists(), Ok(true)) {
+        continue;
+      }
+
+      let Some(workspace) = WorkspaceRoot::read_or_warn(&root) else {
+        continue;
+      };
+
+      let relative = dir.strip_prefix(ancestor).unwrap_or(dir);
+      if workspace.is_excluded(relative) {
+        continue;
+      }
+
+      // Cargo refuses to build a package that is under a workspace without being part of it.
+      return workspace.is_member(relative).then_some(workspace);
+    }
+
+    None
+  }
+
+  fn is_inherited(&self, dependency: &Dependency, cfg: Option<&str>) -> bool {
+    let tables = match cfg {
+      Some(cfg) => self.target.as_ref().and_then(|it| it.get(cfg)),
+      None => Some(&self.dependencies),
+    };
+
+    tables
+      .and_then(|it| it.get(dependency.kind))
+      .and_then(|deps| deps.get(&dependency.name))
+      .is_some_and(is_workspace)
+  }
+
+  fn add_dependencies(
+    &self,
+    tree: &mut DependencyTree,
+    deps: &HashMap<String, Value>,
+    kind: DependencyKind,
+    cfg: Option<&str>,
+  ) {
+    let rust_version = self.rust_version();
+    for parsed in self.parse_dependencies(deps) {
+      let index = match self.config.resolve(parsed.registry) {
+        Ok(index) => index,
+        Err(err) => {
+          tree
+            .unresolved
+            .push((parsed.name.to_owned(), err.to_string()));
+          continue;
+        }
+      };
+
+      if let Ok(comparator) = Comparator::parse(parsed.version) {
+        let dependency = tree.add_renamed(parsed.name, parsed.package, comparator, kind);
+        if let Some(index) = index {
+          dependency.registry_url = Some(index.url);
+          dependency.registry_token = index.token;
+        }
+
+        dependency.target = cfg.map(ToOwned::to_owned);
+        dependency.features = parsed.features;
+        dependency.rust_version.clone_from(&rust_version);
+      }
+    }
+  }
+
+  /// Dependencies in `deps` that are published to a registry.
+  fn parse_dependencies<'a>(
+    &'a self,
+    deps: &'a HashMap<String, Value>,
+  ) -> Vec<ParsedDependency<'a>> {
+    let mut dependencies = Vec::with_capacity(deps.len());
+    for (name, value) in deps {
+      let (value, extra) = if is_workspace(value) {
+        let inherited = self
+          .root
+          .as_ref()
+          .and_then(|it| it.dependencies.get(name));
+
+        let Some(inherited) = inherited else { continue };
+        (inherited, Some(value))
+      } else {
+        (value, None)
+      };
+
+      // Members inheriting a dependency may enable features on top of the workspace ones.
+      let features = [Some(value), extra]
+        .into_iter()
+        .flatten()
+        .filter_map(|it| it.get("features").and_then(Value::as_array))
+        .flatten()
+        .filter_map(Value::as_str)
+        .map(ToOwned::to_owned)
+        .sorted_unstable()
+        .dedup()
+        .collect_vec();
+
+      if let Some(version) = parse_version(value) {
+        dependencies.push(ParsedDependency {
+          name: name.as_str(),
+          package: value.get("package").and_then(Value::as_str),
+          registry: value.get("registry").and_then(Value::as_str),
+          version: version.as_str(),
+          features,
+        });
+      }
+    }
+
+    dependencies
+  }
+
+  /// Minimum Rust version of the package, which may be inherited from the workspace.
+  fn rust_version(&self) -> Option<Version> {
+    let version = match self.package.as_ref()?.rust_version.as_ref()? {
+      MaybeWorkspace::Value(version) => version,
+      MaybeWorkspace::Workspace { .. } => self.root.as_ref()?.rust_version.as_ref()?,
+    };
+
+    parse_partial(version).ok()
   }
 }
 
-fn format(value: &Value) -> Result<String> {
-  let contents = toml::to_string(value)?;
-  let options = formatter::Options {
-    column_width: 120,
-    ..Default::default()
-  };
+impl DependencyTables {
+  const KINDS: [DependencyKind; 3] = [
+    DependencyKind::Normal,
+    DependencyKind::Development,
+    DependencyKind::Build,
+  ];
+
+  fn get(&self, kind: DependencyKind) -> Option<&HashMap<String, Value>> {
+    match kind {
+      DependencyKind::Normal => self.dependencies.as_ref(),
+      DependencyKind::Development => self.dev_dependencies.as_ref(),
+      DependencyKind::Build => self.build_dependencies.as_ref(),
+      _ => None,
+    }
+  }
+}
+
+impl WorkspaceRoot {
+  fn new(path: &Path, workspace: CargoWorkspace, dependencies: &DependencyTables) -> Self {
+    let dir = path.parent().unwrap_or(path);
+    let path_dependencies = DependencyTables::KINDS
+      .into_iter()
+      .filter_map(|kind| dependencies.get(kind))
+      .flat_map(HashMap::values)
+      .filter_map(|it| it.get("path").and_then(Value::as_str))
+      .map(|it| normalize(&dir.join(it)))
+      .collect();
+
+    let package = workspace.package;
+    Self {
+      path: path.to_path_buf(),
+      members: workspace.members,
+      exclude: workspace.exclude,
+      path_dependencies,
+      version: package.as_ref().and_then(|it| it.version.clone()),
+      rust_version: package.and_then(|it| it.rust_version),
+      dependencies: workspace.dependencies.unwrap_or_default(),
+    }
+  }
+
+  fn read(path: &Path) -> Result<Option<Self>> {
+    let contents = fs::read_to_string(path)?;
+    let manifest: WorkspaceManifest = toml::from_str(&contents)?;
+    let root = manifest
+      .workspace
+      .map(|it| Self::new(path, it, &manifest.dependencies));
+
+    Ok(root)
+  }
+
+  /// Reads the workspace at `path`, warning about it instead of failing,
+  /// as the package may still be used on its own.
+  fn read_or_warn(path: &Path) -> Option<Self> {
+    WorkspaceRoot::read(path).unwrap_or_else(|err| {
+      let warning = format!("ignored workspace at {}:", path.display()).bright_yellow();
+      println!("{warning} {err}");
+      None
+    })
+  }
+
+  /// Whether the package at `dir`, relative to the root, is left out of the workspace.
+  /// An excluded directory may still hold members listed explicitly.
+  fn is_excluded(&self, dir: &Path) -> bool {
+    let excluded = self.exclude.iter().any(|it| dir.starts_with(it));
+    let listed = self.members.iter().any(|it| dir.starts_with(it));
+    excluded && !listed
+  }
+
+  /// Whether the package at `dir`, relative to the root, is matched by `members`,
+  /// or is a path dependency of the root package.
+  fn is_member(&self, dir: &Path) -> bool {
+    let root = self.path.parent().unwrap_or(&self.path);
+    if self
+      .path_dependencies
+      .contains(&normalize(&root.join(dir)))
+    {
+      return true;
+    }
+
+    let mut builder = GlobSetBuilder::new();
+    for member in &self.members {
+      let member = member.trim_end_matches('/');
+      if let Ok(glob) = GlobBuilder::new(member)
+        .literal_separator(true)
+        .build()
+      {
+        builder.add(glob);
+      }
+    }
 
-  let contents = formatter::format(&contents, options);
-  Ok(contents)
+    let relative = dir
+      .components()
+      .map(|it| it.as_os_str().to_string_lossy())
+      .join("/");
+
+    builder
+      .build()
+      .is_ok_and(|it| it.is_match(relative))
+  }
 }
 
-fn parse_dependencies(deps: &HashMap<String, Value>) -> HashMap<String, String> {
-  let mut dependencies = HashMap::with_capacity(deps.len());
-  for (name, version) in deps {
-    if let Some(version) = parse_version(version) {
-      dependencies.insert(name.clone(), version.clone());
+/// Removes the `.` and `..` components of `path`, without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+  let mut normalized = PathBuf::new();
+  for component in path.components() {
+    match component {
+      Component::CurDir => {}
+      Component::ParentDir if normalized.file_name().is_some() => {
+        normalized.pop();
+      }
+      _ => normalized.push(component),
     }
   }
 
-  dependencies
+  normalized
+}
+
+fn is_workspace(value: &Value) -> bool {
+  value
+    .get("workspace")
+    .and_then(Value::as_bool)
+    .unwrap_or(false)
 }
 
 fn parse_version(value: &Value) -> Option<&String> {
@@ -171,3 +656,126 @@ fn parse_version(value: &Value) -> Option<&String> {
 
   None
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::package::{Package, VersionSource};
+  use crate::testing::TempDir;
+
+  const ROOT: &str = r#"
+[workspace]
+members = ["crates/*"]
+exclude = ["crates/excluded"]
+
+[workspace.package]
+version = "1.2.3"
+
+[workspace.dependencies]
+serde = { version = "1.0.100", features = ["derive"] }
+"#;
+
+  fn member(name: &str) -> String {
+    format!(
+      r#"
+[package]
+name = "{name}"
+version.workspace = true
+
+[dependencies]
+serde.workspace = true
+"#
+    )
+  }
+
+  #[test]
+  fn inherits_the_workspace_version() {
+    let dir = TempDir::new();
+    dir.write("Cargo.toml", ROOT);
+    let path = dir.write("crates/a/Cargo.toml", &member("a"));
+
+    let package = Package::new(&path).unwrap();
+    assert_eq!(package.version, Version::new(1, 2, 3));
+    assert_eq!(package.workspace(), Some(dir.path()));
+  }
+
+  #[test]
+  fn resolves_workspace_dependencies() {
+    let dir = TempDir::new();
+    dir.write("Cargo.toml", ROOT);
+    let path = dir.write("crates/a/Cargo.toml", &member("a"));
+
+    let tree = CargoToml::read(&path).unwrap().dependency_tree();
+    let [serde] = tree.dependencies.as_slice() else {
+      panic!("expected one dependency");
+    };
+
+    assert_eq!(serde.name, "serde");
+    assert_eq!(serde.comparator.to_string(), "^1.0.100");
+    assert_eq!(serde.kind, DependencyKind::Normal);
+  }
+
+  #[test]
+  fn shares_the_version_source_of_members() {
+    let dir = TempDir::new();
+    let root = dir.write("Cargo.toml", ROOT);
+    let a = Package::new(dir.write("crates/a/Cargo.toml", &member("a"))).unwrap();
+    let b = Package::new(dir.write("crates/b/Cargo.toml", &member("b"))).unwrap();
+
+    assert_eq!(a.version_source(), VersionSource::Workspace(root));
+    assert_eq!(a.version_source(), b.version_source());
+  }
+
+  #[test]
+  fn checks_workspace_membership() {
+    let dir = TempDir::new();
+    dir.write("Cargo.toml", ROOT);
+
+    let standalone = "[package]\nname = \"standalone\"\nversion = \"0.1.0\"\n";
+    let excluded = dir.write("crates/excluded/Cargo.toml", standalone);
+    let outside = dir.write("tools/Cargo.toml", standalone);
+    let nested = dir.write("crates/a/nested/Cargo.toml", standalone);
+
+    for path in [excluded, outside, nested] {
+      let package = Package::new(&path).unwrap();
+      assert_eq!(package.workspace(), None, "{}", path.display());
+      assert_eq!(package.version, Version::new(0, 1, 0));
+    }
+  }
+
+  #[test]
+  fn counts_path_dependencies_as_members() {
+    let dir = TempDir::new();
+    dir.write(
+      "Cargo.toml",
+      r#"
+[package]
+name = "root"
+version = "1.0.0"
+
+[dependencies]
+helper = { path = "./helper" }
+
+[workspace]
+"#,
+    );
+    let path = dir.write("helper/Cargo.toml", "[package]\nname = \"helper\"\n");
+
+    let package = Package::new(&path).unwrap();
+    assert_eq!(package.workspace(), Some(dir.path()));
+  }
+
+  #[test]
+  fn ignores_unreadable_workspaces() {
+    let dir = TempDir::new();
+    dir.write("Cargo.toml", "[workspace\nmembers = [");
+    let path = dir.write(
+      "a/Cargo.toml",
+      "[package]\nname = \"a\"\nversion = \"0.1.0\"\n",
+    );
+
+    let package = Package::new(&path).unwrap();
+    assert_eq!(package.workspace(), None);
+    assert_eq!(package.version, Version::new(0, 1, 0));
+  }
+}
@@ -0,0 +1,248 @@
+use anyhow::Result;
+use semver::{Op, Version};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::yaml::YamlDocument;
+use super::{parse_pinned_constraint, replace_version};
+use crate::agent::Agent;
+use crate::config::Config;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// <https://helm.sh/docs/topics/charts/#the-chartyaml-file>
+#[derive(Deserialize)]
+pub(super) struct ChartYaml {
+  pub name: String,
+  pub version: String,
+
+  #[serde(default)]
+  pub dependencies: Vec<ChartDependency>,
+
+  /// Manifest whose version `appVersion` follows, if set in `miho.toml`.
+  #[serde(skip)]
+  app_version_from: Option<PathBuf>,
+
+  #[serde(skip)]
+  dir: PathBuf,
+}
+
+/// <https://helm.sh/docs/topics/charts/#the-dependencies-field>
+#[derive(Deserialize)]
+pub(super) struct ChartDependency {
+  pub name: String,
+  pub version: Option<String>,
+  pub repository: Option<String>,
+  pub alias: Option<String>,
+}
+
+impl Manifest for ChartYaml {
+  type Value = YamlDocument;
+
+  const FILENAME: &'static str = "Chart.yaml";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+    let mut manifest: ChartYaml = serde_yaml::from_str(&contents)?;
+
+    manifest.dir = path.parent().unwrap_or(path).to_path_buf();
+    manifest.app_version_from = Config::read(&manifest.dir)?
+      .helm_chart
+      .app_version_from
+      .map(|it| manifest.dir.join(it));
+
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    YamlDocument::read(path)
+  }
+}
+
+impl Handler for ChartYaml {
+  fn agent(&self) -> Agent {
+    Agent::Helm
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let mut manifest = ChartYaml::read_as_value(&package.path)?;
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for dependency in &self.dependencies {
+      let Some(repository) = self.repository_url(dependency) else {
+        continue;
+      };
+
+      let comparator = dependency
+        .version
+        .as_deref()
+        .and_then(parse_pinned_constraint)
+        .filter(|it| it.op != Op::Wildcard);
+
+      if let Some(comparator) = comparator {
+        let name = dependency
+          .alias
+          .as_ref()
+          .unwrap_or(&dependency.name);
+        let kind = DependencyKind::Normal;
+        let added = tree.add_renamed(name, Some(&dependency.name), comparator, kind);
+        added.registry_url = Some(repository);
+      }
+    }
+
+    tree
+  }
+
+  fn follow(&self, package: &Package, version: &Version) -> Result<()> {
+    let mut manifest = ChartYaml::read_as_value(&package.path)?;
+    manifest.set_string(&["appVersion"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn leader(&self) -> Option<&Path> {
+    self.app_version_from.as_deref()
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = ChartYaml::read_as_value(&package.path)?;
+
+    for target in targets {
+      let name = target.dependency.name.as_str();
+      let found = self
+        .dependencies
+        .iter()
+        .enumerate()
+        .find(|(_, it)| it.alias.as_ref().unwrap_or(&it.name) == name);
+
+      if let Some((index, dependency)) = found
+        && let Some(version) = &dependency.version
+      {
+        let index = index.to_string();
+        let version = replace_version(version, &target.comparator);
+        manifest.set_string(&["dependencies", &index, "version"], &version)?;
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    Version::parse(&self.version).map_err(Into::into)
+  }
+}
+
+impl ChartYaml {
+  /// URL of the chart repository, where relative `file://` paths are resolved against the chart.
+  /// Local charts and OCI registries have no `index.yaml`, so they are skipped.
+  fn repository_url(&self, dependency: &ChartDependency) -> Option<String> {
+    let repository = dependency.repository.as_deref()?;
+
+    if let Some(path) = repository.strip_prefix("file://") {
+      let dir = self.dir.join(path);
+      return dir
+        .join("index.yaml")
+        .is_file()
+        .then(|| format!("file://{}", dir.display()));
+    }
+
+    repository
+      .starts_with("http")
+      .then(|| repository.trim_end_matches('/').to_owned())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::package::FollowerEdit;
+  use crate::testing::TempDir;
+
+  const CHART: &str = r#"apiVersion: v2
+name: demo
+# Bumped by Miho.
+version: "1.2.3"
+appVersion: 0.1.0
+dependencies:
+  - name: redis
+    version: ~17.3.0
+    repository: https://charts.example.com/
+  - name: postgresql
+    alias: db
+    version: 12.1.0
+    repository: oci://registry.example.com/charts
+"#;
+
+  #[test]
+  fn reads_charts() {
+    let dir = TempDir::new();
+    let path = dir.write("Chart.yaml", CHART);
+
+    let manifest = ChartYaml::read(&path).unwrap();
+    assert_eq!(manifest.name(), "demo");
+    assert_eq!(manifest.version().unwrap(), Version::new(1, 2, 3));
+    assert_eq!(manifest.leader(), None);
+
+    let tree = manifest.dependency_tree();
+    let [redis] = tree.dependencies.as_slice() else {
+      panic!("expected one dependency");
+    };
+
+    assert_eq!(redis.name, "redis");
+    assert_eq!(redis.comparator.to_string(), "~17.3.0");
+    assert_eq!(
+      redis.registry_url.as_deref(),
+      Some("https://charts.example.com")
+    );
+  }
+
+  #[test]
+  fn bumps_charts() {
+    let dir = TempDir::new();
+    let path = dir.write("Chart.yaml", CHART);
+
+    let package = Package::new(&path).unwrap();
+    package
+      .manifest
+      .bump(&package, Version::new(1, 3, 0))
+      .unwrap();
+
+    assert_eq!(dir.read("Chart.yaml"), CHART.replace("1.2.3", "1.3.0"));
+  }
+
+  #[test]
+  fn follows_the_app_version() {
+    let dir = TempDir::new();
+    let leader = dir.write(
+      "app/package.json",
+      r#"{ "name": "app", "version": "0.1.0" }"#,
+    );
+    dir.write(
+      "chart/miho.toml",
+      "[helm-chart]\napp-version-from = \"../app/package.json\"\n",
+    );
+    let chart = dir.write("chart/Chart.yaml", CHART);
+
+    let packages = [
+      Package::new(&leader).unwrap(),
+      Package::new(&chart).unwrap(),
+    ];
+    let bumped = [(&packages[0], Version::new(0, 2, 0))];
+    let edits = FollowerEdit::find(&bumped, &packages);
+    FollowerEdit::apply(&edits).unwrap();
+
+    let expected = CHART.replace("appVersion: 0.1.0", "appVersion: 0.2.0");
+    assert_eq!(dir.read("chart/Chart.yaml"), expected);
+  }
+}
@@ -0,0 +1,236 @@
+use anyhow::Result;
+use semver::{Comparator, Version};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::path::Path;
+
+use super::json::JsonDocument;
+use super::{DEFAULT_VERSION, dir_name, version_text};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// <https://getcomposer.org/doc/04-schema.md>
+#[derive(Deserialize)]
+pub(super) struct ComposerJson {
+  pub name: Option<String>,
+  pub version: Option<String>,
+  pub require: Option<HashMap<String, String>>,
+
+  #[serde(rename(deserialize = "require-dev"))]
+  pub require_dev: Option<HashMap<String, String>>,
+
+  #[serde(skip)]
+  dir_name: String,
+}
+
+/// The part of a Composer constraint miho can update, which is its last alternative,
+/// e.g. `^2.0` in `^1.0 || ^2.0`.
+///
+/// <https://getcomposer.org/doc/articles/versions.md#writing-version-constraints>
+struct Constraint {
+  comparator: Comparator,
+  /// Exclusive bound the comparator cannot express, such as the `<1.0` of `~0.2`.
+  upper_bound: Option<Version>,
+  /// Span of the version in the whole constraint, without any `v` prefix.
+  version: Range<usize>,
+}
+
+impl Manifest for ComposerJson {
+  type Value = JsonDocument;
+
+  const FILENAME: &'static str = "composer.json";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let document = JsonDocument::read(path)?;
+    let mut manifest: ComposerJson = serde_json::from_str(document.as_str())?;
+    manifest.dir_name = dir_name(path);
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    JsonDocument::read(path)
+  }
+}
+
+impl Handler for ComposerJson {
+  fn agent(&self) -> Agent {
+    Agent::Composer
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let mut manifest = ComposerJson::read_as_value(&package.path)?;
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for (kind, dependencies) in [
+      (DependencyKind::Normal, &self.require),
+      (DependencyKind::Development, &self.require_dev),
+    ] {
+      for (name, spec) in dependencies.iter().flatten() {
+        // Platform packages, such as `php` or `ext-json`, are not in the registry.
+        if !name.contains('/') {
+          continue;
+        }
+
+        if let Some(constraint) = Constraint::parse(spec) {
+          let dependency = tree.add(name, constraint.comparator, kind);
+          dependency.upper_bound = constraint.upper_bound;
+        }
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    self.name.as_deref().unwrap_or(&self.dir_name)
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = ComposerJson::read_as_value(&package.path)?;
+
+    for target in targets {
+      let (key, dependencies) = match target.dependency.kind {
+        DependencyKind::Normal => ("require", &self.require),
+        DependencyKind::Development => ("require-dev", &self.require_dev),
+        _ => continue,
+      };
+
+      let name = target.dependency.name.as_str();
+      let spec = dependencies.as_ref().and_then(|it| it.get(name));
+
+      if let Some(spec) = spec
+        && let Some(constraint) = Constraint::parse(spec)
+      {
+        let mut spec = spec.clone();
+        spec.replace_range(constraint.version, &version_text(&target.comparator));
+        manifest.set_string(&[key, name], &spec)?;
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    match &self.version {
+      Some(version) => {
+        let version = version.strip_prefix('v').unwrap_or(version);
+        Version::parse(version).map_err(Into::into)
+      }
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+}
+
+impl Constraint {
+  fn parse(spec: &str) -> Option<Self> {
+    let start = spec.rfind('|').map_or(0, |it| it + 1);
+    let alternative = &spec[start..];
+    let start = start + alternative.len() - alternative.trim_start().len();
+
+    // Stability flags, such as `@beta`, are kept as they are.
+    let alternative = alternative.trim();
+    let alternative = &alternative[..alternative.find('@').unwrap_or(alternative.len())];
+
+    // Ranges, such as `>=1.0 <2.0` or `1.0 - 2.0`, have more than one bound.
+    if alternative.is_empty()
+      || alternative.contains([' ', ','])
+      || alternative.starts_with("dev-")
+      || alternative.ends_with("-dev")
+      || alternative == "*"
+    {
+      return None;
+    }
+
+    let op = ["^", "~", ">=", "==", "="]
+      .into_iter()
+      .find(|it| alternative.starts_with(it))
+      .unwrap_or_default();
+
+    let version = &alternative[op.len()..];
+    let prefix = usize::from(version.starts_with('v'));
+    let version = &version[prefix..];
+
+    // Composer's `~X.Y` means `>=X.Y <X+1.0`, which is not `^0.Y` when `X` is zero,
+    // while `~X.Y.Z` means `>=X.Y.Z <X.Y+1.0`, just like semver's `~`.
+    let (op, upper_bound) = match op {
+      "~" if version.split('.').count() == 2 => {
+        let major = version.split('.').next()?.parse::<u64>().ok()?;
+        (">=", Some(Version::new(major + 1, 0, 0)))
+      }
+      "~" | "^" | ">=" => (op, None),
+      "" if version.contains('*') => ("", None),
+      "" | "=" | "==" => ("=", None),
+      _ => return None,
+    };
+
+    let comparator = Comparator::parse(&format!("{op}{version}")).ok()?;
+    let start = start + alternative.len() - version.len();
+
+    Some(Self {
+      comparator,
+      upper_bound,
+      version: start..start + version.len(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Parses `spec` into its comparator, upper bound and the version miho would replace.
+  fn parse(spec: &str) -> Option<(String, Option<Version>, &str)> {
+    let constraint = Constraint::parse(spec)?;
+    let version = &spec[constraint.version];
+    Some((
+      constraint.comparator.to_string(),
+      constraint.upper_bound,
+      version,
+    ))
+  }
+
+  #[test]
+  fn parses_constraints() {
+    let bound = |major| Some(Version::new(major, 0, 0));
+    let cases = [
+      ("^1.2.3", Some(("^1.2.3", None, "1.2.3"))),
+      ("^0.3", Some(("^0.3", None, "0.3"))),
+      ("~1.2.3", Some(("~1.2.3", None, "1.2.3"))),
+      ("~1.2", Some((">=1.2", bound(2), "1.2"))),
+      ("~0.2", Some((">=0.2", bound(1), "0.2"))),
+      ("~1", Some(("~1", None, "1"))),
+      (">=2.0", Some((">=2.0", None, "2.0"))),
+      ("1.2.3", Some(("=1.2.3", None, "1.2.3"))),
+      ("v1.2.3", Some(("=1.2.3", None, "1.2.3"))),
+      ("==1.2.3", Some(("=1.2.3", None, "1.2.3"))),
+      ("1.2.*", Some(("1.2.*", None, "1.2.*"))),
+      ("^1.0 || ^2.0", Some(("^2.0", None, "2.0"))),
+      ("^1.0|^2.1", Some(("^2.1", None, "2.1"))),
+      ("^2.0@beta", Some(("^2.0", None, "2.0"))),
+      ("^1.0 || dev-main", None),
+      ("dev-main", None),
+      ("1.0.x-dev", None),
+      ("2.0-dev", None),
+      (">=1.0 <2.0", None),
+      ("1.0 - 2.0", None),
+      ("*", None),
+      ("<2.0", None),
+      ("", None),
+    ];
+
+    for (spec, expected) in cases {
+      let expected =
+        expected.map(|(comparator, bound, version)| (comparator.to_owned(), bound, version));
+      assert_eq!(parse(spec), expected, "{spec}");
+    }
+  }
+}
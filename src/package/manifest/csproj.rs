@@ -0,0 +1,250 @@
+use anyhow::{Result, bail};
+use semver::{Comparator, Op, Version};
+use std::path::{Path, PathBuf};
+
+use super::xml::XmlDocument;
+use super::{DEFAULT_VERSION, version_text};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+const DIRECTORY_BUILD_PROPS: &str = "Directory.Build.props";
+const PACKAGE_REFERENCE: [&str; 3] = ["Project", "ItemGroup", "PackageReference"];
+const VERSION: [&str; 3] = ["Project", "PropertyGroup", "Version"];
+const VERSION_PREFIX: [&str; 3] = ["Project", "PropertyGroup", "VersionPrefix"];
+const VERSION_SUFFIX: [&str; 3] = ["Project", "PropertyGroup", "VersionSuffix"];
+
+/// An `MSBuild` project, either a `*.csproj` or a `Directory.Build.props` shared by the projects below it.
+///
+/// <https://learn.microsoft.com/dotnet/core/project-sdk/msbuild-props#package-properties>
+/// <https://learn.microsoft.com/visualstudio/msbuild/customize-by-directory>
+pub(super) struct Csproj {
+  name: String,
+  version: Option<String>,
+  /// `Directory.Build.props` the version is inherited from, if the project has none.
+  props: Option<PathBuf>,
+  references: Vec<PackageReference>,
+}
+
+/// <https://learn.microsoft.com/nuget/consume-packages/package-references-in-project-files>
+struct PackageReference {
+  name: String,
+  version: String,
+}
+
+impl Manifest for Csproj {
+  type Value = XmlDocument;
+
+  const FILENAME: &'static str = "*.csproj";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref().canonicalize()?;
+    let document = Csproj::read_as_value(&path)?;
+    let text = |path: &[&str]| document.find(path).map(|it| document.text(it));
+    let is_props = path.ends_with(DIRECTORY_BUILD_PROPS);
+
+    let name = text(&["Project", "PropertyGroup", "PackageId"])
+      .or_else(|| text(&["Project", "PropertyGroup", "AssemblyName"]))
+      .map(ToOwned::to_owned)
+      .or_else(|| {
+        let name = if is_props {
+          path.parent()?.file_name()?
+        } else {
+          path.file_stem()?
+        };
+
+        Some(name.to_string_lossy().into_owned())
+      })
+      .unwrap_or_default();
+
+    let mut version = read_version(&document);
+    let mut props = None;
+
+    if version.is_none() && !is_props {
+      let found = path
+        .ancestors()
+        .skip(1)
+        .map(|it| it.join(DIRECTORY_BUILD_PROPS))
+        .find(|it| it.is_file());
+
+      if let Some(found) = found {
+        version = read_version(&XmlDocument::read(&found)?);
+        props = version.is_some().then_some(found);
+      }
+    }
+
+    let references = document
+      .find_all(&PACKAGE_REFERENCE)
+      .filter_map(|element| {
+        let name = document
+          .attribute(element, "Include")
+          .or_else(|| document.attribute(element, "Update"))?;
+
+        let version = document
+          .attribute(element, "Version")
+          .or_else(|| Some(document.text(document.child(element, "Version")?)))?;
+
+        Some(PackageReference {
+          name: name.to_owned(),
+          version: version.to_owned(),
+        })
+      })
+      .collect();
+
+    let manifest = Csproj { name, version, props, references };
+
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    XmlDocument::read(path)
+  }
+}
+
+impl Handler for Csproj {
+  fn agent(&self) -> Agent {
+    Agent::Dotnet
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let path = match (&self.version, &self.props) {
+      (Some(_), Some(props)) => props.as_path(),
+      (Some(_), None) => package.path.as_path(),
+      (None, _) => bail!("{} has no version", self.name),
+    };
+
+    let mut manifest = Csproj::read_as_value(path)?;
+    write_version(&mut manifest, &version)?;
+    manifest.write(path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for reference in &self.references {
+      if let Some(comparator) = parse_version_range(&reference.version) {
+        tree.add(&reference.name, comparator, DependencyKind::Normal);
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = Csproj::read_as_value(&package.path)?;
+
+    for target in targets {
+      let name = target.dependency.name.as_str();
+      let Some(element) = manifest
+        .find_all(&PACKAGE_REFERENCE)
+        .find(|it| {
+          manifest
+            .attribute(it, "Include")
+            .or_else(|| manifest.attribute(it, "Update"))
+            .is_some_and(|it| it.eq_ignore_ascii_case(name))
+        })
+        .cloned()
+      else {
+        continue;
+      };
+
+      let version = match target.comparator.op {
+        Op::Exact => format!("[{}]", version_text(&target.comparator)),
+        _ => version_text(&target.comparator),
+      };
+
+      if manifest.attribute(&element, "Version").is_some() {
+        manifest.set_attribute(&element, "Version", &version)?;
+      } else if let Some(child) = manifest.child(&element, "Version").cloned() {
+        manifest.set_element_text(&child, &version)?;
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    match &self.version {
+      Some(version) => Version::parse(version).map_err(Into::into),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+
+  fn workspace_root(&self) -> Option<&Path> {
+    self.props.as_deref()
+  }
+}
+
+/// Reads `Version`, or else `VersionPrefix` followed by `VersionSuffix`, as `dotnet pack` does.
+fn read_version(document: &XmlDocument) -> Option<String> {
+  if let Some(version) = document.find(&VERSION) {
+    return Some(document.text(version).to_owned());
+  }
+
+  let prefix = document.text(document.find(&VERSION_PREFIX)?);
+  match document
+    .find(&VERSION_SUFFIX)
+    .map(|it| document.text(it))
+  {
+    Some(suffix) if !suffix.is_empty() => Some(format!("{prefix}-{suffix}")),
+    _ => Some(prefix.to_owned()),
+  }
+}
+
+/// Writes `version` to `Version` if the project has it. Otherwise, the prerelease goes to
+/// `VersionSuffix`, which is added after `VersionPrefix` when missing.
+fn write_version(manifest: &mut XmlDocument, version: &Version) -> Result<()> {
+  if manifest.find(&VERSION).is_some() {
+    return manifest.set_text(&VERSION, &version.to_string());
+  }
+
+  let Some(prefix_element) = manifest.find(&VERSION_PREFIX).cloned() else {
+    bail!("missing xml element: {}", VERSION_PREFIX.join("/"));
+  };
+
+  let mut prefix = Version::new(version.major, version.minor, version.patch).to_string();
+  let mut suffix = version.pre.to_string();
+
+  if !version.build.is_empty() {
+    let tail = if suffix.is_empty() {
+      &mut prefix
+    } else {
+      &mut suffix
+    };
+    tail.push('+');
+    tail.push_str(version.build.as_str());
+  }
+
+  if manifest.find(&VERSION_SUFFIX).is_some() {
+    manifest.set_text(&VERSION_SUFFIX, &suffix)?;
+  } else if !suffix.is_empty() {
+    manifest.insert_after(&prefix_element, "VersionSuffix", &suffix)?;
+  }
+
+  manifest.set_text(&VERSION_PREFIX, &prefix)
+}
+
+/// A bare version is a minimum, while `[1.0.0]` is an exact pin. Other ranges,
+/// floating versions and `MSBuild` properties are left alone.
+///
+/// <https://learn.microsoft.com/nuget/concepts/package-versioning#version-ranges>
+fn parse_version_range(version: &str) -> Option<Comparator> {
+  let version = version.trim();
+  if let Some(exact) = version
+    .strip_prefix('[')
+    .and_then(|it| it.strip_suffix(']'))
+  {
+    return Comparator::parse(&format!("={}", exact.trim())).ok();
+  }
+
+  if version.starts_with(|it: char| it.is_ascii_digit()) && !version.contains('*') {
+    return Comparator::parse(&format!(">={version}")).ok();
+  }
+
+  None
+}
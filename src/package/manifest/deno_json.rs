@@ -0,0 +1,138 @@
+use anyhow::Result;
+use semver::{Comparator, Version};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use super::json::JsonDocument;
+use super::{DEFAULT_VERSION, dir_name};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// Either `deno.json` or `jsr.json`.
+///
+/// <https://docs.deno.com/runtime/fundamentals/configuration>
+/// <https://jsr.io/docs/package-configuration>
+#[derive(Deserialize)]
+pub(super) struct DenoJson {
+  pub name: Option<String>,
+  pub version: Option<String>,
+  pub imports: Option<HashMap<String, String>>,
+
+  #[serde(skip)]
+  dir_name: String,
+}
+
+/// A `jsr:` or `npm:` specifier, such as `jsr:@std/path@^1.0.0/posix`.
+struct Specifier<'a> {
+  protocol: &'a str,
+  name: &'a str,
+  version: &'a str,
+  subpath: &'a str,
+}
+
+impl Manifest for DenoJson {
+  type Value = JsonDocument;
+
+  const FILENAME: &'static str = "deno.json";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let document = JsonDocument::read(path)?;
+    let mut manifest: DenoJson = serde_json::from_str(document.as_str())?;
+    manifest.dir_name = dir_name(path);
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    JsonDocument::read(path)
+  }
+}
+
+impl Handler for DenoJson {
+  fn agent(&self) -> Agent {
+    Agent::Deno
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let mut manifest = DenoJson::read_as_value(&package.path)?;
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for (alias, spec) in self.imports.iter().flatten() {
+      if let Some(specifier) = Specifier::parse(spec)
+        && let Ok(comparator) = Comparator::parse(specifier.version)
+      {
+        let kind = DependencyKind::Import;
+        let dependency = tree.add_renamed(alias, Some(specifier.name), comparator, kind);
+        dependency.registry = Some(specifier.registry());
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    self.name.as_deref().unwrap_or(&self.dir_name)
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = DenoJson::read_as_value(&package.path)?;
+
+    for target in targets {
+      let alias = target.dependency.name.as_str();
+      let specifier = self
+        .imports
+        .as_ref()
+        .and_then(|it| it.get(alias))
+        .and_then(|it| Specifier::parse(it));
+
+      if let Some(specifier) = specifier {
+        let Specifier { protocol, name, subpath, .. } = specifier;
+        let spec = format!("{protocol}{name}@{}{subpath}", target.comparator);
+        manifest.set_string(&["imports", alias], &spec)?;
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    match &self.version {
+      Some(version) => Version::parse(version).map_err(Into::into),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+}
+
+impl<'a> Specifier<'a> {
+  fn parse(spec: &'a str) -> Option<Self> {
+    let protocol = ["jsr:", "npm:"]
+      .into_iter()
+      .find(|it| spec.starts_with(it))?;
+
+    let rest = &spec[protocol.len()..];
+    let rest = rest.strip_prefix('/').unwrap_or(rest);
+
+    // Scoped names start with `@`, so the version separator is the next one.
+    let at = rest.get(1..)?.find('@')? + 1;
+    let (name, rest) = (&rest[..at], &rest[at + 1..]);
+    let (version, subpath) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+
+    Some(Self { protocol, name, version, subpath })
+  }
+
+  fn registry(&self) -> Agent {
+    if self.protocol == "jsr:" {
+      Agent::Deno
+    } else {
+      Agent::Npm
+    }
+  }
+}
@@ -0,0 +1,362 @@
+use anyhow::{Result, bail};
+use semver::{Comparator, Op, Version};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use super::DEFAULT_VERSION;
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+use crate::version::ComparatorExt;
+
+/// A Go module, whose version is only recorded by git tags.
+///
+/// <https://go.dev/ref/mod#go-mod-file>
+/// <https://go.dev/ref/mod#vcs-version>
+pub(super) struct GoMod {
+  module: String,
+  requires: Vec<Require>,
+  /// Prefix of the tags of this module, e.g. `tools/` for a module in the `tools` directory.
+  tag_prefix: String,
+  version: Option<Version>,
+}
+
+struct Require {
+  path: String,
+  version: Version,
+}
+
+impl Manifest for GoMod {
+  type Value = String;
+
+  const FILENAME: &'static str = "go.mod";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let contents = GoMod::read_as_value(path)?;
+
+    let Some(module) = directives(&contents)
+      .find(|(verb, _)| *verb == "module")
+      .and_then(|(_, args)| args.split_whitespace().next())
+    else {
+      bail!("{} has no module directive", path.display());
+    };
+
+    let module = unquote(module).to_owned();
+    let requires = directives(&contents)
+      .filter(|(verb, args)| *verb == "require" && !args.contains("// indirect"))
+      .filter_map(|(_, args)| Require::parse(args))
+      .collect();
+
+    let dir = path
+      .parent()
+      .filter(|it| !it.as_os_str().is_empty())
+      .unwrap_or(Path::new("."));
+    let tag_prefix = tag_prefix(dir, &module);
+    let version = latest_tag(dir, &tag_prefix, major_suffix(&module));
+
+    let manifest = GoMod {
+      module,
+      requires,
+      tag_prefix,
+      version,
+    };
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    fs::read_to_string(path).map_err(Into::into)
+  }
+}
+
+impl Handler for GoMod {
+  fn agent(&self) -> Agent {
+    Agent::Go
+  }
+
+  /// The version itself is recorded by [`Handler::tag`].
+  fn bump(&self, _: &Package, _: Version) -> Result<()> {
+    Ok(())
+  }
+
+  /// A new major version changes the module path, which every import of the module's own
+  /// packages refers to, and may live in a `vN` directory of its own.
+  fn check_bump(&self, version: &Version) -> Result<()> {
+    let current = major_suffix(&self.module).unwrap_or(1);
+    if version.major.max(1) != current {
+      bail!(
+        "v{} changes the module path to {}, which must be done by hand",
+        version.major,
+        module_path(&self.module, version.major)
+      );
+    }
+
+    Ok(())
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for require in &self.requires {
+      // Go selects the minimum version that satisfies every requirement,
+      // and other major versions live under a different module path.
+      let comparator = Comparator::from_version(&require.version, Op::GreaterEq);
+      tree.add(&require.path, comparator, DependencyKind::Normal);
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    &self.module
+  }
+
+  fn tag(&self, version: &Version) -> Option<String> {
+    Some(format!("{}v{version}", self.tag_prefix))
+  }
+
+  fn is_unreleased(&self) -> bool {
+    self.version.is_none()
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let contents = GoMod::read_as_value(&package.path)?;
+    let mut output = String::with_capacity(contents.len());
+    let mut in_block = false;
+
+    for line in contents.split_inclusive('\n') {
+      let trimmed = line.trim();
+      let args = if in_block {
+        in_block = !trimmed.starts_with(')');
+        Some(trimmed)
+      } else if let Some(args) = trimmed.strip_prefix("require") {
+        in_block = args.trim_start().starts_with('(');
+        Some(args.trim_start())
+      } else {
+        None
+      };
+
+      let target = args.and_then(Require::parse).and_then(|require| {
+        targets
+          .iter()
+          .find(|it| it.dependency.name == require.path)
+          .map(|it| (require, &it.comparator))
+      });
+
+      match target {
+        Some((require, comparator)) => {
+          let old = format!("v{}", require.version);
+          let new = format!("v{}", comparator.as_version()?);
+          output.push_str(&line.replacen(&old, &new, 1));
+        }
+        None => output.push_str(line),
+      }
+    }
+
+    fs::write(&package.path, output)?;
+
+    Ok(())
+  }
+
+  fn version(&self) -> Result<Version> {
+    Ok(self.version.clone().unwrap_or(DEFAULT_VERSION))
+  }
+}
+
+impl Require {
+  /// Parses the arguments of a `require` directive, such as `golang.org/x/mod v0.14.0`.
+  fn parse(args: &str) -> Option<Self> {
+    let args = args.split("//").next()?;
+    let mut args = args.split_whitespace();
+    let path = unquote(args.next()?);
+    let version = Version::parse(args.next()?.strip_prefix('v')?).ok()?;
+
+    // Pseudo-versions point at a commit and are not listed by the proxy.
+    if is_pseudo_version(&version) || !version.build.is_empty() {
+      return None;
+    }
+
+    Some(Self { path: path.to_owned(), version })
+  }
+}
+
+/// Yields each directive as `(verb, args)`, expanding `verb ( ... )` blocks.
+/// Trailing comments are kept, since `// indirect` is meaningful.
+fn directives(contents: &str) -> impl Iterator<Item = (&str, &str)> {
+  let mut block: Option<&str> = None;
+  contents.lines().filter_map(move |line| {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with("//") {
+      return None;
+    }
+
+    if let Some(verb) = block {
+      if line.starts_with(')') {
+        block = None;
+        return None;
+      }
+
+      return Some((verb, line));
+    }
+
+    let (verb, args) = line.split_once(char::is_whitespace)?;
+    let args = args.trim();
+    if args
+      .split("//")
+      .next()
+      .is_some_and(|it| it.trim() == "(")
+    {
+      block = Some(verb);
+      return None;
+    }
+
+    Some((verb, args))
+  })
+}
+
+fn unquote(value: &str) -> &str {
+  value.trim_matches(['"', '`'])
+}
+
+/// <https://go.dev/ref/mod#pseudo-versions>
+fn is_pseudo_version(version: &Version) -> bool {
+  version
+    .pre
+    .split(['.', '-'])
+    .any(|it| it.len() == 14 && it.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// Major version in the `/vN` suffix of the module path, if any.
+///
+/// <https://go.dev/ref/mod#major-version-suffixes>
+fn major_suffix(module: &str) -> Option<u64> {
+  let (_, last) = module.rsplit_once('/')?;
+  let major = last.strip_prefix('v')?.parse().ok()?;
+  (major >= 2).then_some(major)
+}
+
+/// Path of `module` for the `major` version.
+fn module_path(module: &str, major: u64) -> String {
+  let base = match major_suffix(module) {
+    Some(_) => module
+      .rsplit_once('/')
+      .map_or("", |(base, _)| base),
+    None => module,
+  };
+
+  if major <= 1 {
+    base.to_owned()
+  } else {
+    format!("{base}/v{major}")
+  }
+}
+
+/// Tags of a module in a subdirectory are prefixed with its path relative to the repository root.
+/// A `vN` directory holding the major version itself is not part of the prefix.
+fn tag_prefix(dir: &Path, module: &str) -> String {
+  let relative = repository_root(dir)
+    .zip(dir.canonicalize().ok())
+    .and_then(|(root, dir)| dir.strip_prefix(root).ok().map(Path::to_path_buf))
+    .unwrap_or_default();
+
+  let mut components = relative
+    .components()
+    .map(|it| it.as_os_str().to_string_lossy().into_owned())
+    .collect::<Vec<_>>();
+
+  if let Some(major) = major_suffix(module)
+    && components
+      .last()
+      .is_some_and(|it| *it == format!("v{major}"))
+  {
+    components.pop();
+  }
+
+  let mut prefix = components.join("/");
+  if !prefix.is_empty() {
+    prefix.push('/');
+  }
+
+  prefix
+}
+
+fn repository_root(dir: &Path) -> Option<PathBuf> {
+  let output = Command::new("git")
+    .args(["rev-parse", "--show-toplevel"])
+    .current_dir(dir)
+    .output()
+    .ok()?;
+
+  if !output.status.success() {
+    return None;
+  }
+
+  let root = String::from_utf8_lossy(&output.stdout);
+  PathBuf::from(root.trim()).canonicalize().ok()
+}
+
+/// Finds the highest version among the tags of the module.
+fn latest_tag(dir: &Path, prefix: &str, major: Option<u64>) -> Option<Version> {
+  let pattern = format!("{prefix}v*");
+  let output = Command::new("git")
+    .args(["tag", "--list", &pattern])
+    .current_dir(dir)
+    .output()
+    .ok()?;
+
+  let stdout = String::from_utf8_lossy(&output.stdout);
+  stdout
+    .lines()
+    .filter_map(|tag| Version::parse(tag.strip_prefix(prefix)?.strip_prefix('v')?).ok())
+    .filter(|version| match major {
+      Some(major) => version.major == major,
+      None => version.major <= 1,
+    })
+    .max_by(Version::cmp_precedence)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn module(path: &str) -> GoMod {
+    GoMod {
+      module: path.to_owned(),
+      requires: Vec::new(),
+      tag_prefix: String::new(),
+      version: None,
+    }
+  }
+
+  #[test]
+  fn reads_major_suffixes() {
+    assert_eq!(major_suffix("example.com/mod"), None);
+    assert_eq!(major_suffix("example.com/mod/v1"), None);
+    assert_eq!(major_suffix("example.com/mod/v2"), Some(2));
+    assert_eq!(major_suffix("example.com/mod/v10"), Some(10));
+    assert_eq!(major_suffix("example.com/mod/vendor"), None);
+    assert_eq!(major_suffix("gopkg.in/yaml.v3"), None);
+  }
+
+  #[test]
+  fn moves_module_paths() {
+    assert_eq!(module_path("example.com/mod", 1), "example.com/mod");
+    assert_eq!(module_path("example.com/mod", 2), "example.com/mod/v2");
+    assert_eq!(module_path("example.com/mod/v2", 3), "example.com/mod/v3");
+    assert_eq!(module_path("example.com/mod/v2", 1), "example.com/mod");
+  }
+
+  #[test]
+  fn refuses_new_major_versions() {
+    let v1 = module("example.com/mod");
+    assert!(v1.check_bump(&Version::new(0, 2, 0)).is_ok());
+    assert!(v1.check_bump(&Version::new(1, 0, 0)).is_ok());
+    assert!(v1.check_bump(&Version::new(2, 0, 0)).is_err());
+
+    let v2 = module("example.com/mod/v2");
+    assert!(v2.check_bump(&Version::new(2, 1, 0)).is_ok());
+    assert!(v2.check_bump(&Version::new(3, 0, 0)).is_err());
+  }
+}
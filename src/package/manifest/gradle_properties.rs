@@ -0,0 +1,159 @@
+use anyhow::{Result, bail};
+use semver::Version;
+use std::fs;
+use std::path::Path;
+
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// A Gradle project whose version is set in `gradle.properties`.
+/// Its name is the one of the directory, as Gradle does by default.
+///
+/// <https://docs.gradle.org/current/userguide/build_environment.html#sec:gradle_configuration_properties>
+pub(super) struct GradleProperties {
+  name: String,
+  version: String,
+}
+
+impl Manifest for GradleProperties {
+  type Value = String;
+
+  const FILENAME: &'static str = "gradle.properties";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let contents = GradleProperties::read_as_value(path)?;
+
+    let name = path
+      .canonicalize()?
+      .parent()
+      .and_then(Path::file_name)
+      .map(|it| it.to_string_lossy().into_owned())
+      .unwrap_or_default();
+
+    // Projects also keep settings such as `org.gradle.jvmargs` in this file,
+    // which only makes it a package when it sets the version.
+    let Some(version) = contents
+      .lines()
+      .find_map(|line| Some(property(line)?.1))
+      .map(ToOwned::to_owned)
+    else {
+      bail!("{} has no version", path.display());
+    };
+
+    Ok(Box::new(GradleProperties { name, version }))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    fs::read_to_string(path).map_err(Into::into)
+  }
+}
+
+impl Handler for GradleProperties {
+  fn agent(&self) -> Agent {
+    Agent::Gradle
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let contents = GradleProperties::read_as_value(&package.path)?;
+    let mut found = false;
+
+    let mut lines = contents
+      .lines()
+      .map(|line| match property(line) {
+        Some((prefix, _)) if !found => {
+          found = true;
+          format!("{prefix}{version}")
+        }
+        _ => line.to_owned(),
+      })
+      .collect::<Vec<_>>()
+      .join("\n");
+
+    if !found {
+      bail!("{} has no version", package.path.display());
+    }
+
+    if contents.ends_with('\n') {
+      lines.push('\n');
+    }
+
+    fs::write(&package.path, lines).map_err(Into::into)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    DependencyTree::new(self.agent())
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, _: &Package, _: &[dependency::Target]) -> Result<()> {
+    Ok(())
+  }
+
+  fn version(&self) -> Result<Version> {
+    Version::parse(&self.version).map_err(Into::into)
+  }
+}
+
+/// Splits a `version` property into everything before its value, and the value itself.
+/// Keys and values may be separated by `=`, `:` or whitespace.
+///
+/// <https://docs.oracle.com/javase/8/docs/api/java/util/Properties.html#load-java.io.Reader->
+fn property(line: &str) -> Option<(&str, &str)> {
+  let rest = line.trim_start().strip_prefix("version")?;
+  let value = rest.trim_start_matches([' ', '\t']);
+  let value = value
+    .strip_prefix(['=', ':'])
+    .map_or(value, |it| it.trim_start_matches([' ', '\t']));
+
+  // Rules out keys such as `versionCode`.
+  if value.len() == rest.len() && !rest.is_empty() {
+    return None;
+  }
+
+  let prefix = &line[..line.len() - value.len()];
+  Some((prefix, value.trim_end()))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+
+  #[test]
+  fn reads_version_properties() {
+    let cases = [
+      ("version=1.2.3", Some(("version=", "1.2.3"))),
+      ("version = 1.2.3 ", Some(("version = ", "1.2.3"))),
+      ("  version: 1.2.3", Some(("  version: ", "1.2.3"))),
+      ("version 1.2.3", Some(("version ", "1.2.3"))),
+      ("versionCode=3", None),
+      ("org.gradle.jvmargs=-Xmx2g", None),
+    ];
+
+    for (line, expected) in cases {
+      assert_eq!(property(line), expected, "{line}");
+    }
+  }
+
+  #[test]
+  fn requires_a_version() {
+    let dir = TempDir::new();
+    let app = dir.write(
+      "app/gradle.properties",
+      "org.gradle.jvmargs=-Xmx2g\nversion=1.2.3\n",
+    );
+    let root = dir.write("gradle.properties", "org.gradle.jvmargs=-Xmx2g\n");
+
+    let package = Package::new(&app).unwrap();
+    assert_eq!(package.name, "app");
+    assert_eq!(package.version, Version::new(1, 2, 3));
+
+    assert!(Package::new(&root).is_err());
+  }
+}
@@ -0,0 +1,333 @@
+use anyhow::{Result, anyhow, bail};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+const BOM: char = '\u{feff}';
+
+/// A JSON file that is edited in place, so indentation, key order,
+/// line endings, BOM and the final newline are kept as they are.
+pub(super) struct JsonDocument {
+  contents: String,
+  bom: bool,
+}
+
+impl JsonDocument {
+  pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let contents = fs::read_to_string(path)?;
+    Ok(Self::parse(contents))
+  }
+
+  pub fn parse(mut contents: String) -> Self {
+    let bom = contents.starts_with(BOM);
+    if bom {
+      contents.remove(0);
+    }
+
+    Self { contents, bom }
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.contents
+  }
+
+  pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    let mut contents = String::with_capacity(self.contents.len() + BOM.len_utf8());
+    if self.bom {
+      contents.push(BOM);
+    }
+
+    contents.push_str(&self.contents);
+    fs::write(path, contents)?;
+
+    Ok(())
+  }
+
+  /// Sets the string at `path`, inserting the key in its parent object if needed.
+  pub fn set_string(&mut self, path: &[&str], value: &str) -> Result<()> {
+    let value = serde_json::to_string(value)?;
+    self.set_raw(path, &value)
+  }
+
+  /// Sets the raw JSON `value` at `path`, inserting the key in its parent object if needed.
+  pub fn set_raw(&mut self, path: &[&str], value: &str) -> Result<()> {
+    let Some((key, parent)) = path.split_last() else {
+      bail!("cannot replace the root of a json document");
+    };
+
+    let root = Scanner::new(&self.contents).root()?;
+    if let Some(range) = self.find(root.clone(), path) {
+      self.contents.replace_range(range, value);
+      return Ok(());
+    }
+
+    let parent_range = self
+      .find(root, parent)
+      .ok_or_else(|| anyhow!("missing json object: {}", parent.join(".")))?;
+
+    let members = Scanner::new(&self.contents).members(parent_range.clone())?;
+    let newline = self.newline();
+    let indent = self.indent();
+    let inner = indent.repeat(path.len());
+    let key = serde_json::to_string(key)?;
+
+    if let Some(last) = members.last() {
+      let member = format!(",{newline}{inner}{key}: {value}");
+      self.contents.insert_str(last.value.end, &member);
+    } else {
+      let outer = indent.repeat(parent.len());
+      let object = format!("{{{newline}{inner}{key}: {value}{newline}{outer}}}");
+      self.contents.replace_range(parent_range, &object);
+    }
+
+    Ok(())
+  }
+
+  fn find(&self, mut range: Range<usize>, path: &[&str]) -> Option<Range<usize>> {
+    for segment in path {
+      let members = Scanner::new(&self.contents).members(range).ok()?;
+
+      range = members
+        .into_iter()
+        .find(|it| it.key == *segment)?
+        .value;
+    }
+
+    Some(range)
+  }
+
+  fn newline(&self) -> &'static str {
+    if self.contents.contains("\r\n") {
+      "\r\n"
+    } else {
+      "\n"
+    }
+  }
+
+  /// Detects the indentation unit from the first indented line, defaulting to two spaces.
+  fn indent(&self) -> String {
+    self
+      .contents
+      .lines()
+      .skip(1)
+      .map(|line| {
+        let trimmed = line.trim_start_matches([' ', '\t']);
+        &line[..line.len() - trimmed.len()]
+      })
+      .find(|it| !it.is_empty())
+      .unwrap_or("  ")
+      .to_owned()
+  }
+}
+
+struct Member {
+  key: String,
+  value: Range<usize>,
+}
+
+/// Minimal JSON scanner that only tracks where each value lives in the source.
+struct Scanner<'a> {
+  bytes: &'a [u8],
+  source: &'a str,
+  pos: usize,
+}
+
+impl<'a> Scanner<'a> {
+  fn new(source: &'a str) -> Self {
+    Self {
+      bytes: source.as_bytes(),
+      source,
+      pos: 0,
+    }
+  }
+
+  fn root(&mut self) -> Result<Range<usize>> {
+    self.skip_whitespace();
+    self.value()
+  }
+
+  fn members(&mut self, object: Range<usize>) -> Result<Vec<Member>> {
+    self.pos = object.start;
+    self.expect(b'{')?;
+
+    let mut members = Vec::new();
+    loop {
+      self.skip_whitespace();
+      match self.peek() {
+        Some(b'}') => break,
+        Some(b',') => {
+          self.pos += 1;
+          continue;
+        }
+        _ => {}
+      }
+
+      let key = self.string()?;
+      let key: String = serde_json::from_str(&self.source[key])?;
+
+      self.skip_whitespace();
+      self.expect(b':')?;
+      self.skip_whitespace();
+
+      let value = self.value()?;
+      members.push(Member { key, value });
+    }
+
+    Ok(members)
+  }
+
+  fn value(&mut self) -> Result<Range<usize>> {
+    match self.peek() {
+      Some(b'"') => self.string(),
+      Some(open @ (b'{' | b'[')) => self.nested(open),
+      Some(_) => {
+        let start = self.pos;
+        while let Some(byte) = self.peek() {
+          if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+            break;
+          }
+
+          self.pos += 1;
+        }
+
+        Ok(start..self.pos)
+      }
+      None => bail!("unexpected end of json"),
+    }
+  }
+
+  fn string(&mut self) -> Result<Range<usize>> {
+    let start = self.pos;
+    self.expect(b'"')?;
+
+    while let Some(byte) = self.peek() {
+      self.pos += 1;
+      match byte {
+        b'\\' => self.pos += 1,
+        b'"' => return Ok(start..self.pos),
+        _ => {}
+      }
+    }
+
+    bail!("unterminated json string")
+  }
+
+  fn nested(&mut self, open: u8) -> Result<Range<usize>> {
+    let start = self.pos;
+    let mut depth = 0usize;
+
+    while let Some(byte) = self.peek() {
+      match byte {
+        b'"' => {
+          self.string()?;
+          continue;
+        }
+        b'{' | b'[' => depth += 1,
+        b'}' | b']' => {
+          depth -= 1;
+          if depth == 0 {
+            self.pos += 1;
+            return Ok(start..self.pos);
+          }
+        }
+        _ => {}
+      }
+
+      self.pos += 1;
+    }
+
+    bail!("unterminated json {}", char::from(open))
+  }
+
+  fn expect(&mut self, byte: u8) -> Result<()> {
+    if self.peek() == Some(byte) {
+      self.pos += 1;
+      Ok(())
+    } else {
+      bail!("expected `{}` at byte {}", char::from(byte), self.pos)
+    }
+  }
+
+  fn peek(&self) -> Option<u8> {
+    self.bytes.get(self.pos).copied()
+  }
+
+  fn skip_whitespace(&mut self) {
+    while self
+      .peek()
+      .is_some_and(|it| it.is_ascii_whitespace())
+    {
+      self.pos += 1;
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn set(contents: &str, path: &[&str], value: &str) -> String {
+    let mut document = JsonDocument::parse(contents.to_owned());
+    document.set_string(path, value).unwrap();
+    document.as_str().to_owned()
+  }
+
+  #[test]
+  fn replaces_only_the_value() {
+    let contents = "{\n    \"name\": \"a\",\n    \"version\":\"1.0.0\" , \"private\": true\n}";
+    let expected = "{\n    \"name\": \"a\",\n    \"version\":\"1.1.0\" , \"private\": true\n}";
+    assert_eq!(set(contents, &["version"], "1.1.0"), expected);
+  }
+
+  #[test]
+  fn replaces_nested_values() {
+    let contents = r#"{ "dependencies": { "a": "^1.0.0", "b": "~2.0.0" }, "c": [1, "}"] }"#;
+    let expected = r#"{ "dependencies": { "a": "^1.0.0", "b": "~2.1.0" }, "c": [1, "}"] }"#;
+    assert_eq!(set(contents, &["dependencies", "b"], "~2.1.0"), expected);
+  }
+
+  #[test]
+  fn keeps_escaped_keys_and_strings() {
+    let contents = r#"{"a\"b": "\\", "version": "1.0.0"}"#;
+    let expected = r#"{"a\"b": "\\", "version": "2.0.0"}"#;
+    assert_eq!(set(contents, &["version"], "2.0.0"), expected);
+  }
+
+  #[test]
+  fn inserts_with_crlf_and_tabs() {
+    let contents = "{\r\n\t\"name\": \"a\"\r\n}\r\n";
+    let expected = "{\r\n\t\"name\": \"a\",\r\n\t\"version\": \"1.0.0\"\r\n}\r\n";
+    assert_eq!(set(contents, &["version"], "1.0.0"), expected);
+  }
+
+  #[test]
+  fn inserts_into_empty_object() {
+    let contents = "{\n  \"name\": \"a\",\n  \"engines\": {}\n}\n";
+    let expected = "{\n  \"name\": \"a\",\n  \"engines\": {\n    \"node\": \">=20\"\n  }\n}\n";
+    assert_eq!(set(contents, &["engines", "node"], ">=20"), expected);
+  }
+
+  #[test]
+  fn fails_on_missing_parent() {
+    let mut document = JsonDocument::parse("{}".to_owned());
+    assert!(document.set_string(&["a", "b"], "c").is_err());
+  }
+
+  #[test]
+  fn keeps_the_bom() {
+    let path = std::env::temp_dir().join(format!("miho-json-{}.json", std::process::id()));
+    fs::write(&path, "\u{feff}{\"version\": \"1.0.0\"}\n").unwrap();
+
+    let mut document = JsonDocument::read(&path).unwrap();
+    assert_eq!(document.as_str(), "{\"version\": \"1.0.0\"}\n");
+
+    document
+      .set_string(&["version"], "1.0.1")
+      .unwrap();
+    document.write(&path).unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(contents, "\u{feff}{\"version\": \"1.0.1\"}\n");
+  }
+}
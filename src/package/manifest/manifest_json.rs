@@ -0,0 +1,269 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+use super::DEFAULT_VERSION;
+use super::json::JsonDocument;
+use crate::agent::Agent;
+use crate::config::Config;
+use crate::dependency;
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// Browsers accept up to four dot-separated integers, each at most 65535.
+const MAX_VERSION_PART: u64 = 65535;
+
+/// A browser extension manifest, whose `version` only holds integers while
+/// `version_name` keeps the full semver.
+///
+/// <https://developer.chrome.com/docs/extensions/reference/manifest/version>
+/// <https://developer.mozilla.org/docs/Mozilla/Add-ons/WebExtensions/manifest.json/version>
+#[derive(Deserialize)]
+pub(super) struct ManifestJson {
+  /// Only present in extension manifests, which tells them apart from web app manifests.
+  pub manifest_version: u8,
+  pub name: String,
+  pub version: String,
+  pub version_name: Option<String>,
+
+  /// Sibling `package.json` the version follows, if enabled in `miho.toml`.
+  #[serde(skip)]
+  package_json: Option<PathBuf>,
+}
+
+impl Manifest for ManifestJson {
+  type Value = JsonDocument;
+
+  const FILENAME: &'static str = "manifest.json";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let document = JsonDocument::read(path)?;
+    let mut manifest: ManifestJson = serde_json::from_str(document.as_str())?;
+
+    if !(2..=3).contains(&manifest.manifest_version) {
+      bail!(
+        "unsupported manifest_version: {}",
+        manifest.manifest_version
+      );
+    }
+
+    let dir = path.parent().unwrap_or(path);
+    if Config::read(dir)?
+      .web_extension
+      .follow_package_json
+    {
+      manifest.package_json = Some(dir.join("package.json"));
+    }
+
+    // Localized names, such as `__MSG_appName__`, are only resolved by the browser.
+    if manifest.name.starts_with("__MSG_")
+      && let Some(name) = dir.canonicalize()?.file_name()
+    {
+      manifest.name = name.to_string_lossy().into_owned();
+    }
+
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    JsonDocument::read(path)
+  }
+}
+
+impl Handler for ManifestJson {
+  fn agent(&self) -> Agent {
+    Agent::Extension
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let mut manifest = ManifestJson::read_as_value(&package.path)?;
+    manifest.set_string(&["version"], &to_extension_version(&version)?)?;
+    manifest.set_string(&["version_name"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn follow(&self, package: &Package, version: &Version) -> Result<()> {
+    self.bump(package, version.clone())
+  }
+
+  fn leader(&self) -> Option<&Path> {
+    self.package_json.as_deref()
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, _: &Package, _: &[dependency::Target]) -> Result<()> {
+    Ok(())
+  }
+
+  /// Extensions following their `package.json` are only bumped along with it.
+  fn version(&self) -> Result<Version> {
+    if self.package_json.is_some() {
+      return Ok(DEFAULT_VERSION);
+    }
+
+    if let Some(version) = self
+      .version_name
+      .as_deref()
+      .and_then(|it| Version::parse(it).ok())
+    {
+      return Ok(version);
+    }
+
+    let parts = self
+      .version
+      .split('.')
+      .map(str::parse::<u64>)
+      .collect::<Result<Vec<_>, _>>()?;
+
+    match parts.as_slice() {
+      [major] => Ok(Version::new(*major, 0, 0)),
+      [major, minor] => Ok(Version::new(*major, *minor, 0)),
+      [major, minor, patch, ..] if parts.len() <= 4 => Ok(Version::new(*major, *minor, *patch)),
+      _ => bail!("invalid extension version: {}", self.version),
+    }
+  }
+}
+
+/// Converts `version` to the format browsers accept, which compare each part as a number.
+///
+/// As there is no room for a prerelease, it becomes the version right before its release
+/// with a fourth part that keeps the order of the labels, e.g. `1.2.0-beta.3` is `1.1.65535.30003`.
+fn to_extension_version(version: &Version) -> Result<String> {
+  let mut parts = vec![version.major, version.minor, version.patch];
+
+  if !version.pre.is_empty() {
+    let Some(previous) = previous_release(version) else {
+      bail!("{version} has no extension version before its release");
+    };
+
+    parts = previous;
+    parts.push(prerelease_part(version)?);
+  }
+
+  if parts.iter().any(|it| *it > MAX_VERSION_PART) {
+    bail!("{version} does not fit in an extension version");
+  }
+
+  Ok(parts.iter().join("."))
+}
+
+/// Highest three-part version below the release of `version`.
+fn previous_release(version: &Version) -> Option<Vec<u64>> {
+  let max = MAX_VERSION_PART;
+  match (version.major, version.minor, version.patch) {
+    (0, 0, 0) => None,
+    (major, 0, 0) => Some(vec![major - 1, max, max]),
+    (major, minor, 0) => Some(vec![major, minor - 1, max]),
+    (major, minor, patch) => Some(vec![major, minor, patch - 1]),
+  }
+}
+
+/// Fourth part of a prerelease, ordered as semver orders them: numeric ones first,
+/// then `alpha`, `beta` and `rc`, each followed by its number, e.g. `beta.3` is `30003`.
+fn prerelease_part(version: &Version) -> Result<u64> {
+  const STEP: u64 = 10000;
+
+  let identifiers = version.pre.split('.').collect_vec();
+  let (rank, number) = match identifiers.as_slice() {
+    [number] if number.bytes().all(|it| it.is_ascii_digit()) => (Some(1), *number),
+    [label] => (label_rank(label), "0"),
+    [label, number] => (label_rank(label), *number),
+    _ => (None, "0"),
+  };
+
+  let (Some(rank), Ok(number)) = (rank, number.parse::<u64>()) else {
+    bail!("prerelease of {version} has no extension version");
+  };
+
+  if number >= STEP {
+    bail!("prerelease of {version} does not fit in an extension version");
+  }
+
+  Ok(rank * STEP + number)
+}
+
+fn label_rank(label: &str) -> Option<u64> {
+  match label {
+    "alpha" | "a" => Some(2),
+    "beta" | "b" => Some(3),
+    "rc" | "pre" => Some(4),
+    _ => None,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn extension_version(version: &str) -> Result<String> {
+    to_extension_version(&Version::parse(version).unwrap())
+  }
+
+  /// Compares extension versions the way browsers do, part by part as numbers.
+  fn parts(version: &str) -> Vec<u64> {
+    let mut parts = extension_version(version)
+      .unwrap()
+      .split('.')
+      .map(|it| it.parse().unwrap())
+      .collect_vec();
+
+    parts.resize(4, 0);
+    parts
+  }
+
+  #[test]
+  fn maps_versions() {
+    let cases = [
+      ("1.2.3", "1.2.3"),
+      ("1.2.3-beta.3", "1.2.2.30003"),
+      ("1.2.0-alpha.1", "1.1.65535.20001"),
+      ("2.0.0-rc", "1.65535.65535.40000"),
+      ("1.0.0-7", "0.65535.65535.10007"),
+    ];
+
+    for (version, expected) in cases {
+      assert_eq!(extension_version(version).unwrap(), expected, "{version}");
+    }
+  }
+
+  #[test]
+  fn keeps_the_order_of_semver() {
+    let versions = [
+      "1.1.9",
+      "1.2.0-1",
+      "1.2.0-alpha",
+      "1.2.0-alpha.1",
+      "1.2.0-alpha.2",
+      "1.2.0-beta",
+      "1.2.0-beta.1",
+      "1.2.0-beta.3",
+      "1.2.0-rc.1",
+      "1.2.0",
+      "1.2.1-alpha.1",
+      "1.2.1",
+    ];
+
+    for pair in versions.windows(2) {
+      assert!(parts(pair[0]) < parts(pair[1]), "{} < {}", pair[0], pair[1]);
+    }
+  }
+
+  #[test]
+  fn rejects_what_does_not_fit() {
+    for version in [
+      "0.0.0-alpha.1",
+      "1.0.0-nightly.1",
+      "1.0.0-beta.1.2",
+      "1.0.0-beta.10000",
+      "65536.0.0",
+    ] {
+      assert!(extension_version(version).is_err(), "{version}");
+    }
+  }
+}
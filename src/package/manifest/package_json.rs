@@ -2,18 +2,21 @@ use anyhow::Result;
 use itertools::Itertools;
 use semver::{Comparator, Version};
 use serde::Deserialize;
-use serde_json::Value;
 use std::collections::HashMap;
-use std::fs;
 use std::path::Path;
 
 use super::default_version;
+use super::json::JsonDocument;
 use crate::agent::Agent;
 use crate::dependency::{self, DependencyKind, DependencyTree};
 use crate::package::Package;
 use crate::package::manifest::{Handler, Manifest, ManifestBox};
+use crate::package::workspace::NodeWorkspace;
 use crate::version::ComparatorExt;
 
+/// <https://pnpm.io/workspaces#workspace-protocol-workspace>
+const WORKSPACE_PROTOCOL: &str = "workspace:";
+
 #[derive(Deserialize)]
 #[serde(rename_all(deserialize = "camelCase"))]
 pub(super) struct PackageJson {
@@ -25,42 +28,65 @@ pub(super) struct PackageJson {
   pub dependencies: Option<HashMap<String, String>>,
   pub dev_dependencies: Option<HashMap<String, String>>,
   pub peer_dependencies: Option<HashMap<String, String>>,
+
+  /// <https://classic.yarnpkg.com/en/docs/selective-version-resolutions>
+  pub resolutions: Option<HashMap<String, String>>,
+
+  /// Workspace this package belongs to, if any.
+  #[serde(skip)]
+  workspace: Option<NodeWorkspace>,
+
+  /// Agent detected from the lockfile next to the manifest.
+  #[serde(skip)]
+  lockfile_agent: Option<Agent>,
+}
+
+impl PackageJson {
+  fn dependencies_of(&self, kind: DependencyKind) -> Option<&HashMap<String, String>> {
+    match kind {
+      DependencyKind::Normal => self.dependencies.as_ref(),
+      DependencyKind::Development => self.dev_dependencies.as_ref(),
+      DependencyKind::Peer => self.peer_dependencies.as_ref(),
+      DependencyKind::Resolution => self.resolutions.as_ref(),
+      _ => None,
+    }
+  }
 }
 
 impl Manifest for PackageJson {
-  type Value = serde_json::Value;
+  type Value = JsonDocument;
 
   const FILENAME: &'static str = "package.json";
 
   fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
-    let contents = fs::read_to_string(path)?;
-    let manifest: PackageJson = serde_json::from_str(&contents)?;
+    let path = path.as_ref();
+    let document = JsonDocument::read(path)?;
+    let mut manifest: PackageJson = serde_json::from_str(document.as_str())?;
+    manifest.workspace = NodeWorkspace::find(path)?;
+    manifest.lockfile_agent = path.parent().and_then(Agent::from_lockfile);
     Ok(Box::new(manifest))
   }
 
   fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
-    let contents = fs::read_to_string(path)?;
-    let manifest: Self::Value = serde_json::from_str(&contents)?;
-    Ok(manifest)
+    JsonDocument::read(path)
   }
 }
 
 impl Handler for PackageJson {
   fn agent(&self) -> Agent {
-    match &self.package_manager {
-      Some(pm) if pm.starts_with("pnpm") => Agent::Pnpm,
-      _ => Agent::Npm,
-    }
+    self
+      .package_manager
+      .as_deref()
+      .and_then(Agent::from_package_manager)
+      .or_else(|| self.workspace.as_ref()?.agent)
+      .or(self.lockfile_agent)
+      .unwrap_or(Agent::Npm)
   }
 
   fn bump(&self, package: &Package, version: Version) -> Result<()> {
     let mut manifest = PackageJson::read_as_value(&package.path)?;
-    manifest["version"] = Value::String(version.to_string());
-
-    let contents = serde_json::to_string_pretty(&manifest)?;
-    fs::write(&package.path, contents)?;
-
-    Ok(())
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
   }
 
   fn dependency_tree(&self) -> DependencyTree {
@@ -78,6 +104,14 @@ impl Handler for PackageJson {
     add!(&self.dev_dependencies, Development);
     add!(&self.peer_dependencies, Peer);
 
+    for (key, spec) in self.resolutions.iter().flatten() {
+      if let Some(package) = resolution_package(key)
+        && let Ok(comparator) = Comparator::parse(spec)
+      {
+        tree.add_renamed(key, Some(package), comparator, DependencyKind::Resolution);
+      }
+    }
+
     if let Some(pm) = &self.package_manager
       && let Some((name, version)) = pm.split('@').next_tuple()
       && let Ok(comparator) = Comparator::parse(version)
@@ -88,6 +122,26 @@ impl Handler for PackageJson {
     tree
   }
 
+  fn internal_dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for kind in [
+      DependencyKind::Normal,
+      DependencyKind::Development,
+      DependencyKind::Peer,
+    ] {
+      for (name, spec) in self.dependencies_of(kind).into_iter().flatten() {
+        if let Some(version) = spec.strip_prefix(WORKSPACE_PROTOCOL)
+          && let Ok(comparator) = Comparator::parse(version)
+        {
+          tree.add(name, comparator, kind);
+        }
+      }
+    }
+
+    tree
+  }
+
   fn name(&self) -> &str {
     self.name.as_str()
   }
@@ -101,29 +155,64 @@ impl Handler for PackageJson {
         DependencyKind::Development => "devDependencies",
         DependencyKind::Peer => "peerDependencies",
         DependencyKind::PackageManager => "packageManager",
-        DependencyKind::Build => continue,
+        DependencyKind::Resolution => "resolutions",
+        DependencyKind::Build
+        | DependencyKind::Catalog
+        | DependencyKind::Import
+        | DependencyKind::Management
+        | DependencyKind::Parent
+        | DependencyKind::Workspace => continue,
       };
 
       if target.dependency.kind.is_package_manager() {
         let agent = package.agent().to_string().to_lowercase();
         let version = target.comparator.as_version()?;
-        manifest[key] = Value::String(format!("{agent}@{version}"));
-      } else if let Some(deps) = manifest
-        .get_mut(key)
-        .and_then(Value::as_object_mut)
-      {
-        let comparator = Value::String(target.comparator.to_string());
-        deps.insert(target.dependency.name.clone(), comparator);
+        manifest.set_string(&[key], &format!("{agent}@{version}"))?;
+      } else {
+        let name = target.dependency.name.as_str();
+        let mut spec = target.comparator.to_string();
+        if self
+          .dependencies_of(target.dependency.kind)
+          .and_then(|deps| deps.get(name))
+          .is_some_and(|it| it.starts_with(WORKSPACE_PROTOCOL))
+        {
+          spec.insert_str(0, WORKSPACE_PROTOCOL);
+        }
+
+        manifest.set_string(&[key, name], &spec)?;
       }
     }
 
-    let contents = serde_json::to_string_pretty(&manifest)?;
-    fs::write(&package.path, contents)?;
+    manifest.write(&package.path)
+  }
 
-    Ok(())
+  fn workspace_dir(&self) -> Option<&Path> {
+    self
+      .workspace
+      .as_ref()
+      .map(|it| it.root.as_path())
   }
 
   fn version(&self) -> Result<Version> {
     Version::parse(&self.version).map_err(Into::into)
   }
 }
+
+/// Extracts the package name from a resolution key such as `**/lodash` or `a/@scope/b`.
+fn resolution_package(key: &str) -> Option<&str> {
+  let name = match key.rsplit_once('/') {
+    None => key,
+    Some((parent, _)) => {
+      let start = parent.rfind('/').map_or(0, |it| it + 1);
+      if parent[start..].starts_with('@') {
+        &key[start..]
+      } else {
+        &key[parent.len() + 1..]
+      }
+    }
+  };
+
+  // Descriptors such as `lodash@npm:4.17.21` are not supported.
+  let is_valid = !name.is_empty() && !name[1..].contains('@') && name != "**";
+  is_valid.then_some(name)
+}
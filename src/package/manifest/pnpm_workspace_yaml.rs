@@ -0,0 +1,106 @@
+use anyhow::{Result, anyhow};
+use semver::{Comparator, Version};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::yaml::YamlDocument;
+use super::{DEFAULT_VERSION, dir_name};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// <https://pnpm.io/catalogs>
+#[derive(Deserialize)]
+pub(super) struct PnpmWorkspaceYaml {
+  pub catalog: Option<HashMap<String, String>>,
+  pub catalogs: Option<HashMap<String, HashMap<String, String>>>,
+
+  #[serde(skip)]
+  name: String,
+
+  #[serde(skip)]
+  path: PathBuf,
+}
+
+impl Manifest for PnpmWorkspaceYaml {
+  type Value = YamlDocument;
+
+  const FILENAME: &'static str = "pnpm-workspace.yaml";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+    let mut manifest: PnpmWorkspaceYaml = serde_yaml::from_str(&contents)?;
+    manifest.name = dir_name(path);
+    manifest.path = path.to_path_buf();
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    YamlDocument::read(path)
+  }
+}
+
+impl Handler for PnpmWorkspaceYaml {
+  fn agent(&self) -> Agent {
+    Agent::Pnpm
+  }
+
+  fn bump(&self, _: &Package, _: Version) -> Result<()> {
+    Err(anyhow!("{} does not have a version", Self::FILENAME))
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    if let Some(catalog) = &self.catalog {
+      tree.add_many(catalog, DependencyKind::Catalog);
+    }
+
+    for (name, catalog) in self.catalogs.iter().flatten() {
+      for (dependency, version) in catalog {
+        if let Ok(comparator) = Comparator::parse(version) {
+          let kind = DependencyKind::Catalog;
+          tree.add(dependency, comparator, kind).target = Some(name.clone());
+        }
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    self.name.as_str()
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = PnpmWorkspaceYaml::read_as_value(&package.path)?;
+
+    for target in targets {
+      let dependency = target.dependency;
+      if !dependency.kind.is_catalog() {
+        continue;
+      }
+
+      let name = dependency.name.as_str();
+      let comparator = target.comparator.to_string();
+      match dependency.target.as_deref() {
+        Some(catalog) => manifest.set_string(&["catalogs", catalog, name], &comparator)?,
+        None => manifest.set_string(&["catalog", name], &comparator)?,
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    Ok(DEFAULT_VERSION)
+  }
+
+  fn workspace_dir(&self) -> Option<&Path> {
+    self.path.parent()
+  }
+}
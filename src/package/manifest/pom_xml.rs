@@ -0,0 +1,334 @@
+use anyhow::{Result, bail};
+use semver::{Comparator, Version};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use super::DEFAULT_VERSION;
+use super::xml::{Element, XmlDocument};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+
+/// Sections holding dependencies, along with the kind of their entries.
+const SECTIONS: [(&[&str], DependencyKind); 4] = [
+  (
+    &["project", "dependencies", "dependency"],
+    DependencyKind::Normal,
+  ),
+  (
+    &[
+      "project",
+      "dependencyManagement",
+      "dependencies",
+      "dependency",
+    ],
+    DependencyKind::Management,
+  ),
+  (
+    &["project", "build", "plugins", "plugin"],
+    DependencyKind::Build,
+  ),
+  (
+    &["project", "build", "pluginManagement", "plugins", "plugin"],
+    DependencyKind::Build,
+  ),
+];
+
+/// <https://maven.apache.org/pom.html>
+pub(super) struct PomXml {
+  /// Coordinates of the project, as `groupId:artifactId`.
+  name: String,
+  version: Option<String>,
+  parent: Option<Parent>,
+  properties: HashMap<String, String>,
+  dependencies: Vec<MavenDependency>,
+}
+
+/// <https://maven.apache.org/pom.html#Inheritance>
+struct Parent {
+  name: String,
+  version: String,
+  /// Manifest of the parent, if it belongs to the same repository.
+  path: Option<PathBuf>,
+}
+
+struct MavenDependency {
+  name: String,
+  version: String,
+  kind: DependencyKind,
+}
+
+impl Manifest for PomXml {
+  type Value = XmlDocument;
+
+  const FILENAME: &'static str = "pom.xml";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let document = PomXml::read_as_value(path)?;
+    let text = |path: &[&str]| {
+      document
+        .find(path)
+        .map(|it| document.text(it).to_owned())
+    };
+
+    let parent = document
+      .find(&["project", "parent"])
+      .and_then(|parent| {
+        let child = |name| {
+          document
+            .child(parent, name)
+            .map(|it| document.text(it))
+        };
+        let name = format!("{}:{}", child("groupId")?, child("artifactId")?);
+        let relative_path = child("relativePath").unwrap_or("../pom.xml");
+        let dir = path.parent().unwrap_or(path);
+
+        Some(Parent {
+          path: find_parent(&dir.join(relative_path), &name),
+          version: child("version")?.to_owned(),
+          name,
+        })
+      });
+
+    let Some(artifact_id) = text(&["project", "artifactId"]) else {
+      bail!("{} has no artifactId", path.display());
+    };
+
+    let group_id = text(&["project", "groupId"])
+      .or_else(|| {
+        Some(
+          parent
+            .as_ref()?
+            .name
+            .split_once(':')?
+            .0
+            .to_owned(),
+        )
+      })
+      .unwrap_or_default();
+
+    let properties = document
+      .find(&["project", "properties"])
+      .map(|properties| {
+        document
+          .children(properties)
+          .map(|it| {
+            (
+              it.path.last().cloned().unwrap_or_default(),
+              document.text(it).to_owned(),
+            )
+          })
+          .collect()
+      })
+      .unwrap_or_default();
+
+    let mut dependencies = Vec::new();
+    for (section, kind) in SECTIONS {
+      for element in document.find_all(section) {
+        let child = |name| {
+          document
+            .child(element, name)
+            .map(|it| document.text(it))
+        };
+        let (Some(artifact_id), Some(version)) = (child("artifactId"), child("version")) else {
+          continue;
+        };
+
+        // Plugins default to the `org.apache.maven.plugins` group.
+        let group_id = child("groupId").unwrap_or("org.apache.maven.plugins");
+        let kind = if child("scope") == Some("test") {
+          DependencyKind::Development
+        } else {
+          kind
+        };
+
+        dependencies.push(MavenDependency {
+          name: format!("{group_id}:{artifact_id}"),
+          version: version.to_owned(),
+          kind,
+        });
+      }
+    }
+
+    let manifest = PomXml {
+      name: format!("{group_id}:{artifact_id}"),
+      version: text(&["project", "version"]),
+      parent,
+      properties,
+      dependencies,
+    };
+
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    XmlDocument::read(path)
+  }
+}
+
+impl Handler for PomXml {
+  fn agent(&self) -> Agent {
+    Agent::Maven
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let path = match (&self.version, self.workspace_root()) {
+      (Some(_), _) => package.path.as_path(),
+      (None, Some(parent)) => parent,
+      (None, None) => bail!("{} has no version", self.name),
+    };
+
+    let mut manifest = PomXml::read_as_value(path)?;
+    set_version(&mut manifest, &["project", "version"], &version.to_string())?;
+    manifest.write(path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for dependency in &self.dependencies {
+      if let Some(comparator) = self.comparator(&dependency.version) {
+        tree.add(&dependency.name, comparator, dependency.kind);
+      }
+    }
+
+    tree
+  }
+
+  /// Besides dependencies on sibling modules, children must follow the version of their parent.
+  fn internal_dependency_tree(&self) -> DependencyTree {
+    let mut tree = self.dependency_tree();
+
+    if let Some(parent) = &self.parent
+      && let Some(comparator) = self.comparator(&parent.version)
+    {
+      tree.add(&parent.name, comparator, DependencyKind::Parent);
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = PomXml::read_as_value(&package.path)?;
+
+    for target in targets {
+      let version = target.comparator.to_string();
+      let version = version.trim_start_matches('=');
+
+      if target.dependency.kind.is_parent() {
+        set_version(&mut manifest, &["project", "parent", "version"], version)?;
+        continue;
+      }
+
+      set_dependency_version(&mut manifest, &target.dependency.name, version)?;
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    let version = self
+      .version
+      .as_deref()
+      .or_else(|| Some(self.parent.as_ref()?.version.as_str()));
+
+    match version.map(|it| self.resolve(it)) {
+      Some(version) => Version::parse(version).map_err(Into::into),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+
+  fn workspace_root(&self) -> Option<&Path> {
+    if self.version.is_some() {
+      return None;
+    }
+
+    self.parent.as_ref()?.path.as_deref()
+  }
+}
+
+impl PomXml {
+  /// Replaces a `${property}` with its value.
+  fn resolve<'a>(&'a self, value: &'a str) -> &'a str {
+    property_name(value)
+      .and_then(|it| self.properties.get(it))
+      .map_or(value, String::as_str)
+  }
+
+  /// Maven versions are soft requirements, which behave like exact pins in practice.
+  /// Ranges, such as `[1.0,2.0)`, are left alone.
+  fn comparator(&self, version: &str) -> Option<Comparator> {
+    let version = self.resolve(version);
+    if version.starts_with(['[', '(']) {
+      return None;
+    }
+
+    Comparator::parse(&format!("={version}")).ok()
+  }
+}
+
+/// Sets the version at `path`, or the property it refers to, e.g. `${revision}`.
+fn set_version(manifest: &mut XmlDocument, path: &[&str], version: &str) -> Result<()> {
+  let Some(element) = manifest.find(path).cloned() else {
+    bail!("missing xml element: {}", path.join("/"));
+  };
+
+  set_element_version(manifest, &element, version)
+}
+
+fn set_element_version(manifest: &mut XmlDocument, element: &Element, version: &str) -> Result<()> {
+  match property_name(manifest.text(element)).map(ToOwned::to_owned) {
+    Some(property) => manifest.set_text(&["project", "properties", &property], version),
+    None => manifest.set_element_text(element, version),
+  }
+}
+
+/// Sets the version of the dependency or plugin named `groupId:artifactId`.
+fn set_dependency_version(manifest: &mut XmlDocument, name: &str, version: &str) -> Result<()> {
+  let element = SECTIONS
+    .iter()
+    .flat_map(|(section, _)| manifest.find_all(section))
+    .find(|element| {
+      let child = |name| {
+        manifest
+          .child(element, name)
+          .map(|it| manifest.text(it))
+      };
+      let group_id = child("groupId").unwrap_or("org.apache.maven.plugins");
+      let artifact_id = child("artifactId").unwrap_or_default();
+      name == format!("{group_id}:{artifact_id}")
+    })
+    .and_then(|it| manifest.child(it, "version"))
+    .cloned();
+
+  match element {
+    Some(element) => set_element_version(manifest, &element, version),
+    None => Ok(()),
+  }
+}
+
+fn property_name(value: &str) -> Option<&str> {
+  value.strip_prefix("${")?.strip_suffix('}')
+}
+
+/// Returns the manifest at `path` if it is the parent named `name`.
+fn find_parent(path: &Path, name: &str) -> Option<PathBuf> {
+  let path = if path.is_dir() {
+    path.join(PomXml::FILENAME)
+  } else {
+    path.to_path_buf()
+  };
+
+  let document = XmlDocument::read(&path).ok()?;
+  let text = |it: &[&str]| document.find(it).map(|it| document.text(it));
+  let group_id = text(&["project", "groupId"]).or_else(|| text(&["project", "parent", "groupId"]));
+  let coordinates = format!("{}:{}", group_id?, text(&["project", "artifactId"])?);
+
+  (coordinates == name).then_some(path)
+}
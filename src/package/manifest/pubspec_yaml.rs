@@ -0,0 +1,145 @@
+use anyhow::Result;
+use semver::{BuildMetadata, Version};
+use serde::Deserialize;
+use serde_yaml::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use super::yaml::YamlDocument;
+use super::{DEFAULT_VERSION, parse_pinned_constraint, replace_version};
+use crate::agent::Agent;
+use crate::dependency::{self, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+use crate::release::Release;
+use crate::version::VersionExt;
+
+/// <https://dart.dev/tools/pub/pubspec>
+#[derive(Deserialize)]
+pub(super) struct PubspecYaml {
+  pub name: String,
+  pub version: Option<String>,
+  pub dependencies: Option<HashMap<String, Value>>,
+  pub dev_dependencies: Option<HashMap<String, Value>>,
+}
+
+impl Manifest for PubspecYaml {
+  type Value = YamlDocument;
+
+  const FILENAME: &'static str = "pubspec.yaml";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let contents = fs::read_to_string(path)?;
+    let manifest: PubspecYaml = serde_yaml::from_str(&contents)?;
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    YamlDocument::read(path)
+  }
+}
+
+impl Handler for PubspecYaml {
+  fn agent(&self) -> Agent {
+    Agent::Dart
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let mut manifest = PubspecYaml::read_as_value(&package.path)?;
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    for (kind, dependencies) in [
+      (DependencyKind::Normal, &self.dependencies),
+      (DependencyKind::Development, &self.dev_dependencies),
+    ] {
+      for (name, value) in dependencies.iter().flatten() {
+        if let Some(comparator) = constraint(value).and_then(parse_pinned_constraint) {
+          tree.add(name, comparator, kind);
+        }
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  /// Flutter uses the build number as the store version code, so it must keep
+  /// increasing even when no `--build` is given.
+  ///
+  /// <https://docs.flutter.dev/deployment/android#update-the-apps-version-number>
+  fn next_version(&self, version: &Version, release: &Release) -> Version {
+    let mut next = version.with_release(release);
+    if next.build.is_empty()
+      && let Ok(build) = version.build.parse::<u64>()
+    {
+      next.build = BuildMetadata::new(&(build + 1).to_string()).unwrap();
+    }
+
+    next
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = PubspecYaml::read_as_value(&package.path)?;
+
+    for target in targets {
+      let (key, dependencies) = match target.dependency.kind {
+        DependencyKind::Normal => ("dependencies", &self.dependencies),
+        DependencyKind::Development => ("dev_dependencies", &self.dev_dependencies),
+        _ => continue,
+      };
+
+      let name = target.dependency.name.as_str();
+      let Some(value) = dependencies.as_ref().and_then(|it| it.get(name)) else {
+        continue;
+      };
+
+      let Some(constraint) = constraint(value) else {
+        continue;
+      };
+
+      let constraint = replace_version(constraint, &target.comparator);
+
+      if value.is_mapping() {
+        manifest.set_string(&[key, name, "version"], &constraint)?;
+      } else {
+        manifest.set_string(&[key, name], &constraint)?;
+      }
+    }
+
+    manifest.write(&package.path)
+  }
+
+  fn version(&self) -> Result<Version> {
+    match &self.version {
+      Some(version) => Version::parse(version).map_err(Into::into),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+}
+
+/// Returns the version constraint of a hosted dependency, either `^1.0.0`
+/// or `{ hosted: ..., version: ^1.0.0 }`.
+///
+/// <https://dart.dev/tools/pub/dependencies>
+fn constraint(value: &Value) -> Option<&str> {
+  match value {
+    Value::String(constraint) => Some(constraint),
+    Value::Mapping(mapping)
+      if !["sdk", "path", "git"]
+        .iter()
+        .any(|it| mapping.contains_key(it)) =>
+    {
+      mapping.get("version").and_then(Value::as_str)
+    }
+    _ => None,
+  }
+}
@@ -0,0 +1,477 @@
+use anyhow::{Result, anyhow};
+use semver::{Comparator, Version};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+use toml::Value;
+use toml_edit::{DocumentMut, Item};
+
+use super::{
+  DEFAULT_VERSION, parse_pinned_constraint, replace_version, set_toml_string, version_text,
+};
+use crate::agent::Agent;
+use crate::dependency::{self, Dependency, DependencyKind, DependencyTree};
+use crate::package::Package;
+use crate::package::manifest::{Handler, Manifest, ManifestBox};
+use crate::version::pep440;
+
+/// Either a [PEP 621](https://peps.python.org/pep-0621/) project or a Poetry one.
+///
+/// <https://packaging.python.org/en/latest/specifications/pyproject-toml/>
+/// <https://python-poetry.org/docs/pyproject/>
+#[derive(Deserialize)]
+pub(super) struct PyprojectToml {
+  pub project: Option<Project>,
+  pub tool: Option<Tool>,
+
+  /// <https://peps.python.org/pep-0735/>
+  #[serde(rename(deserialize = "dependency-groups"))]
+  pub dependency_groups: Option<HashMap<String, Vec<Value>>>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Project {
+  pub name: String,
+  pub version: Option<String>,
+  pub dependencies: Option<Vec<String>>,
+
+  #[serde(rename(deserialize = "optional-dependencies"))]
+  pub optional_dependencies: Option<HashMap<String, Vec<String>>>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Tool {
+  pub poetry: Option<Poetry>,
+  pub miho: Option<MihoConfig>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct Poetry {
+  pub name: Option<String>,
+  pub version: Option<String>,
+  pub dependencies: Option<HashMap<String, Value>>,
+
+  #[serde(rename(deserialize = "dev-dependencies"))]
+  pub dev_dependencies: Option<HashMap<String, Value>>,
+
+  pub group: Option<HashMap<String, PoetryGroup>>,
+}
+
+#[derive(Deserialize)]
+pub(super) struct PoetryGroup {
+  pub dependencies: Option<HashMap<String, Value>>,
+}
+
+/// Options read from `[tool.miho]`.
+#[derive(Deserialize)]
+pub(super) struct MihoConfig {
+  /// Always write versions in PEP 440 form, even if they are valid semver.
+  #[serde(default)]
+  pub pep440: bool,
+}
+
+/// A [PEP 508](https://peps.python.org/pep-0508/) requirement, such as `requests[socks]>=2.31; python_version > "3.8"`.
+struct Requirement<'a> {
+  name: &'a str,
+  op: &'a str,
+  version: Range<usize>,
+}
+
+impl Manifest for PyprojectToml {
+  type Value = DocumentMut;
+
+  const FILENAME: &'static str = "pyproject.toml";
+
+  fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
+    let path = path.as_ref();
+    let contents = fs::read_to_string(path)?;
+    let manifest: PyprojectToml = toml::from_str(&contents)?;
+
+    if manifest.name().is_empty() {
+      return Err(anyhow!(
+        "{} has neither [project] nor [tool.poetry]",
+        path.display()
+      ));
+    }
+
+    Ok(Box::new(manifest))
+  }
+
+  fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
+    let contents = fs::read_to_string(path)?;
+    let manifest: Self::Value = contents.parse()?;
+    Ok(manifest)
+  }
+}
+
+impl Handler for PyprojectToml {
+  fn agent(&self) -> Agent {
+    Agent::Python
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    let pep440 = self.is_pep440() || version.build.starts_with("post.");
+    let version = if pep440 {
+      pep440::from_semver(&version)?
+    } else {
+      version.to_string()
+    };
+
+    let mut manifest = PyprojectToml::read_as_value(&package.path)?;
+    let item = match self.project_version() {
+      Some(_) => manifest
+        .get_mut("project")
+        .and_then(|it| it.get_mut("version")),
+      None => manifest
+        .get_mut("tool")
+        .and_then(|it| it.get_mut("poetry"))
+        .and_then(|it| it.get_mut("version")),
+    };
+
+    if let Some(item) = item {
+      set_toml_string(item, &version);
+    }
+
+    fs::write(&package.path, manifest.to_string())?;
+
+    Ok(())
+  }
+
+  fn dependency_tree(&self) -> DependencyTree {
+    let mut tree = DependencyTree::new(self.agent());
+
+    if let Some(project) = &self.project {
+      let requirements = project.dependencies.iter().flatten();
+      let requirements = requirements.map(String::as_str);
+      add_requirements(&mut tree, requirements, DependencyKind::Normal, None);
+
+      for (extra, requirements) in project.optional_dependencies.iter().flatten() {
+        let requirements = requirements.iter().map(String::as_str);
+        add_requirements(&mut tree, requirements, DependencyKind::Normal, Some(extra));
+      }
+    }
+
+    for (group, requirements) in self.dependency_groups.iter().flatten() {
+      // Entries may also be `{ include-group = "..." }` tables.
+      let requirements = requirements.iter().filter_map(Value::as_str);
+      add_requirements(
+        &mut tree,
+        requirements,
+        DependencyKind::Development,
+        Some(group),
+      );
+    }
+
+    if let Some(poetry) = self.poetry() {
+      if let Some(deps) = &poetry.dependencies {
+        add_poetry_dependencies(&mut tree, deps, DependencyKind::Normal, None);
+      }
+
+      if let Some(deps) = &poetry.dev_dependencies {
+        add_poetry_dependencies(&mut tree, deps, DependencyKind::Development, None);
+      }
+
+      for (group, tables) in poetry.group.iter().flatten() {
+        if let Some(deps) = &tables.dependencies {
+          add_poetry_dependencies(&mut tree, deps, DependencyKind::Development, Some(group));
+        }
+      }
+    }
+
+    tree
+  }
+
+  fn name(&self) -> &str {
+    match (&self.project, self.poetry()) {
+      (Some(project), _) => &project.name,
+      (None, Some(poetry)) => poetry.name.as_deref().unwrap_or_default(),
+      (None, None) => "",
+    }
+  }
+
+  fn update(&self, package: &Package, targets: &[dependency::Target]) -> Result<()> {
+    let mut manifest = PyprojectToml::read_as_value(&package.path)?;
+
+    for target in targets {
+      let dependency = target.dependency;
+      let group = dependency.target.as_deref();
+      let requirements = match (dependency.kind, group) {
+        (DependencyKind::Normal, None) => Some(vec!["project", "dependencies"]),
+        (DependencyKind::Normal, Some(extra)) => {
+          Some(vec!["project", "optional-dependencies", extra])
+        }
+        (DependencyKind::Development, Some(group)) => Some(vec!["dependency-groups", group]),
+        _ => None,
+      };
+
+      if let Some(path) = requirements
+        && let Some(array) = get_mut(&mut manifest, &path).and_then(Item::as_array_mut)
+      {
+        update_requirements(array, dependency, &target.comparator);
+      }
+
+      let tables = match (dependency.kind, group) {
+        (DependencyKind::Normal, None) => vec!["tool", "poetry", "dependencies"],
+        (DependencyKind::Development, None) => vec!["tool", "poetry", "dev-dependencies"],
+        (DependencyKind::Development, Some(group)) => {
+          vec!["tool", "poetry", "group", group, "dependencies"]
+        }
+        _ => continue,
+      };
+
+      let item = get_mut(&mut manifest, &tables)
+        .and_then(Item::as_table_like_mut)
+        .and_then(|deps| deps.get_mut(&dependency.name));
+
+      if let Some(item) = item {
+        if item.is_str() {
+          set_constraint(item, &target.comparator);
+        } else if let Some(version) = item
+          .as_table_like_mut()
+          .and_then(|it| it.get_mut("version"))
+        {
+          set_constraint(version, &target.comparator);
+        }
+      }
+    }
+
+    fs::write(&package.path, manifest.to_string())?;
+
+    Ok(())
+  }
+
+  fn version(&self) -> Result<Version> {
+    let version = self
+      .project_version()
+      .or_else(|| self.poetry()?.version.as_deref());
+
+    match version {
+      Some(version) if self.is_pep440() => pep440::to_semver(version),
+      Some(version) => Version::parse(version).or_else(|_| pep440::to_semver(version)),
+      None => Ok(DEFAULT_VERSION),
+    }
+  }
+}
+
+impl PyprojectToml {
+  fn poetry(&self) -> Option<&Poetry> {
+    self.tool.as_ref()?.poetry.as_ref()
+  }
+
+  fn project_version(&self) -> Option<&str> {
+    self.project.as_ref()?.version.as_deref()
+  }
+
+  /// Whether versions should be written back in PEP 440 form.
+  fn is_pep440(&self) -> bool {
+    let enabled = self
+      .tool
+      .as_ref()
+      .and_then(|it| it.miho.as_ref())
+      .is_some_and(|it| it.pep440);
+
+    let version = self
+      .project_version()
+      .or_else(|| self.poetry()?.version.as_deref());
+
+    enabled || version.is_some_and(|it| Version::parse(it).is_err())
+  }
+}
+
+fn add_requirements<'a>(
+  tree: &mut DependencyTree,
+  requirements: impl IntoIterator<Item = &'a str>,
+  kind: DependencyKind,
+  group: Option<&String>,
+) {
+  for requirement in requirements {
+    let Some(parsed) = Requirement::parse(requirement) else {
+      continue;
+    };
+
+    if let Some((comparator, upper_bound)) = parsed.comparator(requirement) {
+      let dependency = tree.add(parsed.name, comparator, kind);
+      dependency.upper_bound = upper_bound;
+      dependency.target = group.cloned();
+    }
+  }
+}
+
+fn add_poetry_dependencies(
+  tree: &mut DependencyTree,
+  dependencies: &HashMap<String, Value>,
+  kind: DependencyKind,
+  group: Option<&String>,
+) {
+  for (name, value) in dependencies {
+    if name == "python" {
+      continue;
+    }
+
+    let constraint = match value {
+      Value::String(constraint) => Some(constraint.as_str()),
+      Value::Table(table) if !table.contains_key("path") && !table.contains_key("git") => {
+        table.get("version").and_then(Value::as_str)
+      }
+      _ => None,
+    };
+
+    if let Some(comparator) = constraint.and_then(parse_pinned_constraint) {
+      let dependency = tree.add(name, comparator, kind);
+      dependency.target = group.cloned();
+    }
+  }
+}
+
+fn update_requirements(
+  array: &mut toml_edit::Array,
+  dependency: &Dependency,
+  comparator: &Comparator,
+) {
+  for value in array.iter_mut() {
+    let Some(requirement) = value.as_str() else {
+      continue;
+    };
+
+    let Some(parsed) = Requirement::parse(requirement) else {
+      continue;
+    };
+
+    if normalize_name(parsed.name) == normalize_name(&dependency.name) {
+      let mut updated = requirement.to_owned();
+      updated.replace_range(parsed.version, &version_text(comparator));
+
+      let decor = value.decor().clone();
+      *value = toml_edit::Value::from(updated);
+      *value.decor_mut() = decor;
+    }
+  }
+}
+
+impl<'a> Requirement<'a> {
+  /// Parses a requirement with a single version clause, which is the only kind miho can update.
+  fn parse(requirement: &'a str) -> Option<Self> {
+    let name_end = requirement
+      .find(|it: char| !(it.is_ascii_alphanumeric() || matches!(it, '-' | '_' | '.')))
+      .unwrap_or(requirement.len());
+
+    let name = &requirement[..name_end];
+    let mut pos = name_end + leading_spaces(&requirement[name_end..]);
+
+    if requirement[pos..].starts_with('[') {
+      pos += requirement[pos..].find(']')? + 1;
+      pos += leading_spaces(&requirement[pos..]);
+    }
+
+    let specifier_end = requirement[pos..]
+      .find(';')
+      .map_or(requirement.len(), |it| pos + it);
+
+    let specifier = requirement[pos..specifier_end].trim_end();
+    if name.is_empty() || specifier.contains(',') || specifier.starts_with('(') {
+      return None;
+    }
+
+    let op = ["===", "==", "~=", "!=", "<=", ">=", "<", ">"]
+      .into_iter()
+      .find(|it| specifier.starts_with(it))?;
+
+    let start = pos + op.len() + leading_spaces(&specifier[op.len()..]);
+    let end = pos + specifier.len();
+
+    Some(Self { name, op, version: start..end })
+  }
+
+  /// Maps the clause onto a comparator, where `~=X.Y.Z` behaves like `~X.Y.Z`.
+  /// As `~=X.Y` means `>=X.Y, <X+1`, its upper bound is returned along with it.
+  fn comparator(&self, requirement: &str) -> Option<(Comparator, Option<Version>)> {
+    let version = &requirement[self.version.clone()];
+    let (op, upper_bound) = match self.op {
+      "==" => ("=", None),
+      ">=" => (">=", None),
+      "~=" if version.split('.').count() == 2 => {
+        let major = version.split('.').next()?.parse::<u64>().ok()?;
+        (">=", Some(Version::new(major + 1, 0, 0)))
+      }
+      "~=" => ("~", None),
+      _ => return None,
+    };
+
+    let comparator = Comparator::parse(&format!("{op}{version}")).ok()?;
+    Some((comparator, upper_bound))
+  }
+}
+
+fn leading_spaces(value: &str) -> usize {
+  value.len() - value.trim_start().len()
+}
+
+/// <https://packaging.python.org/en/latest/specifications/name-normalization/>
+pub fn normalize_name(name: &str) -> String {
+  let mut normalized = String::with_capacity(name.len());
+  for part in name
+    .split(['-', '_', '.'])
+    .filter(|it| !it.is_empty())
+  {
+    if !normalized.is_empty() {
+      normalized.push('-');
+    }
+
+    normalized.push_str(&part.to_lowercase());
+  }
+
+  normalized
+}
+
+/// Replaces the version of a Poetry constraint, keeping its operator.
+fn set_constraint(item: &mut Item, comparator: &Comparator) {
+  if let Some(constraint) = item.as_str() {
+    let constraint = replace_version(constraint, comparator);
+    set_toml_string(item, &constraint);
+  }
+}
+
+fn get_mut<'a>(document: &'a mut DocumentMut, path: &[&str]) -> Option<&'a mut Item> {
+  let (first, rest) = path.split_first()?;
+  let mut item = document.get_mut(first)?;
+  for key in rest {
+    item = item.get_mut(key)?;
+  }
+
+  Some(item)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn comparator(requirement: &str) -> Option<(String, Option<Version>)> {
+    let (comparator, upper_bound) = Requirement::parse(requirement)?.comparator(requirement)?;
+    Some((comparator.to_string(), upper_bound))
+  }
+
+  #[test]
+  fn maps_compatible_releases() {
+    let bound = |major| Some(Version::new(major, 0, 0));
+
+    assert_eq!(comparator("a~=0.5"), Some((">=0.5".to_owned(), bound(1))));
+    assert_eq!(comparator("a ~= 1.2"), Some((">=1.2".to_owned(), bound(2))));
+    assert_eq!(comparator("a~=1.2.3"), Some(("~1.2.3".to_owned(), None)));
+  }
+
+  #[test]
+  fn maps_other_clauses() {
+    assert_eq!(
+      comparator("a[extra]==1.0.0"),
+      Some(("=1.0.0".to_owned(), None))
+    );
+    assert_eq!(
+      comparator("a>=2.0 ; python_version < '3.12'"),
+      Some((">=2.0".to_owned(), None))
+    );
+    assert_eq!(comparator("a<2.0"), None);
+    assert_eq!(comparator("a>=1.0,<2.0"), None);
+  }
+}
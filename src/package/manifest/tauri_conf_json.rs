@@ -1,9 +1,9 @@
 use anyhow::{Result, anyhow};
 use semver::Version;
 use serde::Deserialize;
-use std::fs;
 use std::path::Path;
 
+use super::json::JsonDocument;
 use crate::agent::Agent;
 use crate::dependency;
 use crate::package::Package;
@@ -17,20 +17,18 @@ pub struct TauriConfJson {
 }
 
 impl Manifest for TauriConfJson {
-  type Value = serde_json::Value;
+  type Value = JsonDocument;
 
   const FILENAME: &'static str = "tauri.conf.json";
 
   fn read<P: AsRef<Path>>(path: P) -> Result<ManifestBox> {
-    let contents = fs::read_to_string(path)?;
-    let manifest: TauriConfJson = serde_json::from_str(&contents)?;
+    let document = JsonDocument::read(path)?;
+    let manifest: TauriConfJson = serde_json::from_str(document.as_str())?;
     Ok(Box::new(manifest))
   }
 
   fn read_as_value<P: AsRef<Path>>(path: P) -> Result<Self::Value> {
-    let contents = fs::read_to_string(path)?;
-    let manifest: Self::Value = serde_json::from_str(&contents)?;
-    Ok(manifest)
+    JsonDocument::read(path)
   }
 }
 
@@ -41,12 +39,8 @@ impl Handler for TauriConfJson {
 
   fn bump(&self, package: &Package, version: Version) -> Result<()> {
     let mut manifest = TauriConfJson::read_as_value(&package.path)?;
-    manifest["version"] = serde_json::Value::String(version.to_string());
-
-    let contents = serde_json::to_string_pretty(&manifest)?;
-    fs::write(&package.path, contents)?;
-
-    Ok(())
+    manifest.set_string(&["version"], &version.to_string())?;
+    manifest.write(&package.path)
   }
 
   fn name(&self) -> &str {
@@ -0,0 +1,114 @@
+use anyhow::{Result, bail};
+use regex::Regex;
+use semver::Version;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use super::{DEFAULT_VERSION, ManifestBox};
+use crate::agent::Agent;
+use crate::config::VersionFileConfig;
+use crate::dependency;
+use crate::package::Package;
+use crate::package::manifest::Handler;
+
+/// Matches the first word of the file, as in a bare `VERSION` file.
+const DEFAULT_PATTERN: &str = r"\A\s*(\S+)";
+
+/// Any file whose version is found by a regex, as configured in `miho.toml`.
+pub struct VersionFile {
+  name: String,
+  pattern: Regex,
+  source: Option<PathBuf>,
+  version: Option<String>,
+}
+
+impl VersionFile {
+  /// Reads the file at `path`, matched by `config` from the `miho.toml` at `dir`.
+  pub fn read(path: &Path, config: &VersionFileConfig, dir: &Path) -> Result<ManifestBox> {
+    let pattern = config
+      .pattern
+      .as_deref()
+      .unwrap_or(DEFAULT_PATTERN);
+    let pattern = Regex::new(pattern)?;
+
+    if pattern.captures_len() < 2 {
+      bail!("version file pattern has no capture group: {pattern}");
+    }
+
+    let name = config.name.clone().unwrap_or_else(|| {
+      path
+        .strip_prefix(dir)
+        .unwrap_or(path)
+        .to_string_lossy()
+        .replace('\\', "/")
+    });
+
+    let contents = fs::read_to_string(path)?;
+    let version = pattern
+      .captures(&contents)
+      .and_then(|it| it.get(1))
+      .map(|it| it.as_str().trim().to_owned());
+
+    let manifest = VersionFile {
+      name,
+      pattern,
+      source: config.source.as_ref().map(|it| dir.join(it)),
+      version,
+    };
+
+    Ok(Box::new(manifest))
+  }
+
+  /// Replaces every capture of the pattern with `version`.
+  fn write(&self, path: &Path, version: &Version) -> Result<()> {
+    let contents = fs::read_to_string(path)?;
+    let version = version.to_string();
+    let mut output = String::with_capacity(contents.len());
+    let mut last = 0;
+
+    for captures in self.pattern.captures_iter(&contents) {
+      if let Some(group) = captures.get(1) {
+        output.push_str(&contents[last..group.start()]);
+        output.push_str(&version);
+        last = group.end();
+      }
+    }
+
+    output.push_str(&contents[last..]);
+    fs::write(path, output).map_err(Into::into)
+  }
+}
+
+impl Handler for VersionFile {
+  fn agent(&self) -> Agent {
+    Agent::File
+  }
+
+  fn bump(&self, package: &Package, version: Version) -> Result<()> {
+    self.write(&package.path, &version)
+  }
+
+  fn follow(&self, package: &Package, version: &Version) -> Result<()> {
+    self.write(&package.path, version)
+  }
+
+  fn leader(&self) -> Option<&Path> {
+    self.source.as_deref()
+  }
+
+  fn name(&self) -> &str {
+    &self.name
+  }
+
+  fn update(&self, _: &Package, _: &[dependency::Target]) -> Result<()> {
+    Ok(())
+  }
+
+  /// Files following a source package are only rewritten along with it, so they have no version of their own.
+  fn version(&self) -> Result<Version> {
+    match (&self.version, &self.source) {
+      (Some(version), None) => Version::parse(version).map_err(Into::into),
+      _ => Ok(DEFAULT_VERSION),
+    }
+  }
+}
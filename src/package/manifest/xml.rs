@@ -0,0 +1,441 @@
+use anyhow::{Result, bail};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// An XML file that is edited in place, so formatting, comments and
+/// attribute order are kept as they are. Namespaces are ignored.
+pub struct XmlDocument {
+  contents: String,
+  elements: Vec<Element>,
+}
+
+/// An element, identified by the names of its ancestors and its own.
+#[derive(Clone, Debug)]
+pub struct Element {
+  pub path: Vec<String>,
+  /// Offset of the `<` opening the start tag.
+  pub start: usize,
+  /// Span of the content between the start and end tags.
+  pub inner: Range<usize>,
+}
+
+impl XmlDocument {
+  pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let contents = fs::read_to_string(path)?;
+    Self::parse(contents)
+  }
+
+  pub fn parse(contents: String) -> Result<Self> {
+    let elements = scan(&contents)?;
+    Ok(Self { contents, elements })
+  }
+
+  pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    fs::write(path, &self.contents).map_err(Into::into)
+  }
+
+  /// Finds the first element at `path`.
+  pub fn find(&self, path: &[&str]) -> Option<&Element> {
+    self.elements.iter().find(|it| it.path == path)
+  }
+
+  /// Finds every element at `path`, in document order.
+  pub fn find_all<'a>(&'a self, path: &'a [&str]) -> impl Iterator<Item = &'a Element> {
+    self
+      .elements
+      .iter()
+      .filter(move |it| it.path == path)
+  }
+
+  /// Finds the direct child of `parent` named `name`.
+  pub fn child<'a>(&'a self, parent: &'a Element, name: &str) -> Option<&'a Element> {
+    self
+      .children(parent)
+      .find(|it| it.path.last().is_some_and(|it| it == name))
+  }
+
+  /// Finds the direct children of `parent`, in document order.
+  pub fn children<'a>(&'a self, parent: &'a Element) -> impl Iterator<Item = &'a Element> {
+    self.elements.iter().filter(move |it| {
+      it.path.len() == parent.path.len() + 1
+        && parent.inner.start <= it.inner.start
+        && it.inner.end <= parent.inner.end
+    })
+  }
+
+  /// Trimmed text content of an element, if it has no children.
+  pub fn text(&self, element: &Element) -> &str {
+    self.contents[element.inner.clone()].trim()
+  }
+
+  /// Replaces the text of the first element at `path`, keeping the whitespace around it.
+  pub fn set_text(&mut self, path: &[&str], value: &str) -> Result<()> {
+    let Some(element) = self.find(path).cloned() else {
+      bail!("missing xml element: {}", path.join("/"));
+    };
+
+    self.set_element_text(&element, value)
+  }
+
+  /// Replaces the text of `element`, keeping the whitespace around it.
+  pub fn set_element_text(&mut self, element: &Element, value: &str) -> Result<()> {
+    let inner = &self.contents[element.inner.clone()];
+    let start = element.inner.start + inner.len() - inner.trim_start().len();
+    let end = element.inner.start + inner.trim_end().len();
+
+    self.replace(start..end.max(start), &escape(value))
+  }
+
+  /// Value of the attribute `name` of `element`.
+  pub fn attribute(&self, element: &Element, name: &str) -> Option<&str> {
+    self
+      .attribute_span(element, name)
+      .map(|it| &self.contents[it])
+  }
+
+  /// Replaces the value of the attribute `name` of `element`, keeping its quotes.
+  pub fn set_attribute(&mut self, element: &Element, name: &str, value: &str) -> Result<()> {
+    let Some(span) = self.attribute_span(element, name) else {
+      bail!("missing xml attribute: {name}");
+    };
+
+    self.replace(span, &escape(value))
+  }
+
+  /// Inserts a new element with the text `value` right after `element`,
+  /// on its own line and with the same indentation.
+  pub fn insert_after(&mut self, element: &Element, name: &str, value: &str) -> Result<()> {
+    let end = if element.inner.is_empty() && self.contents[..element.inner.end].ends_with("/>") {
+      element.inner.end
+    } else {
+      let Some(offset) = self.contents[element.inner.end..].find('>') else {
+        bail!("unterminated xml tag at byte {}", element.inner.end);
+      };
+
+      element.inner.end + offset + 1
+    };
+
+    let line_start = self.contents[..element.start]
+      .rfind('\n')
+      .map_or(0, |it| it + 1);
+
+    let indent = &self.contents[line_start..element.start];
+    let indent = &indent[..indent.len() - indent.trim_start().len()];
+    let value = format!("\n{indent}<{name}>{}</{name}>", escape(value));
+
+    self.replace(end..end, &value)
+  }
+
+  fn attribute_span(&self, element: &Element, name: &str) -> Option<Range<usize>> {
+    let tag = &self.contents[element.start..element.inner.start];
+    let mut rest = tag;
+
+    while let Some(index) = rest.find('=') {
+      let key = rest[..index].trim_end();
+      let key = key
+        .rsplit(|it: char| it.is_whitespace())
+        .next()
+        .unwrap_or_default();
+
+      let after = &rest[index + 1..];
+      let value = after.trim_start();
+      let quote = value.chars().next()?;
+      let value_start = tag.len() - value.len() + 1;
+      let value_len = value[1..].find(quote)?;
+
+      if key == name {
+        let start = element.start + value_start;
+        return Some(start..start + value_len);
+      }
+
+      rest = &tag[value_start + value_len + 1..];
+    }
+
+    None
+  }
+
+  fn replace(&mut self, range: Range<usize>, value: &str) -> Result<()> {
+    self.contents.replace_range(range, value);
+    self.elements = scan(&self.contents)?;
+
+    Ok(())
+  }
+}
+
+fn escape(value: &str) -> String {
+  value
+    .replace('&', "&amp;")
+    .replace('<', "&lt;")
+    .replace('>', "&gt;")
+    .replace('"', "&quot;")
+}
+
+/// Lists every element of the document along with the span of its content.
+fn scan(source: &str) -> Result<Vec<Element>> {
+  let mut elements = Vec::new();
+  let mut stack: Vec<(String, usize, usize)> = Vec::new();
+  let mut pos = 0;
+
+  while let Some(offset) = source[pos..].find('<') {
+    let start = pos + offset;
+    let rest = &source[start..];
+
+    let skip = [
+      ("<!--", "-->"),
+      ("<![CDATA[", "]]>"),
+      ("<?", "?>"),
+      ("<!", ">"),
+    ]
+    .into_iter()
+    .find(|(open, _)| rest.starts_with(open));
+
+    if let Some((_, close)) = skip {
+      let Some(end) = rest.find(close) else {
+        bail!("unterminated xml markup at byte {start}");
+      };
+
+      pos = start + end + close.len();
+      continue;
+    }
+
+    let Some(end) = tag_end(rest) else {
+      bail!("unterminated xml tag at byte {start}");
+    };
+
+    let tag = &rest[1..end];
+    pos = start + end + 1;
+
+    if let Some(name) = tag.strip_prefix('/') {
+      let name = local_name(name.trim());
+      let Some((open, tag_start, inner_start)) = stack.pop() else {
+        bail!("unexpected closing tag `{name}` at byte {start}");
+      };
+
+      if open != name {
+        bail!("expected closing tag `{open}`, found `{name}` at byte {start}");
+      }
+
+      let mut path = stack
+        .iter()
+        .map(|(it, ..)| it.clone())
+        .collect::<Vec<_>>();
+      path.push(open);
+      elements.push(Element {
+        path,
+        start: tag_start,
+        inner: inner_start..start,
+      });
+    } else {
+      let name = tag
+        .split(|it: char| it.is_whitespace() || it == '/')
+        .next()
+        .unwrap_or_default();
+
+      let name = local_name(name).to_owned();
+      if tag.ends_with('/') {
+        let mut path = stack
+          .iter()
+          .map(|(it, ..)| it.clone())
+          .collect::<Vec<_>>();
+        path.push(name);
+        elements.push(Element { path, start, inner: pos..pos });
+      } else {
+        stack.push((name, start, pos));
+      }
+    }
+  }
+
+  elements.sort_by_key(|it| it.inner.start);
+
+  Ok(elements)
+}
+
+/// Finds the `>` closing a tag, skipping the ones inside quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+  let mut quote = None;
+  for (i, char) in tag.char_indices() {
+    match (char, quote) {
+      ('"' | '\'', None) => quote = Some(char),
+      (c, Some(q)) if c == q => quote = None,
+      ('>', None) => return Some(i),
+      _ => {}
+    }
+  }
+
+  None
+}
+
+fn local_name(name: &str) -> &str {
+  name
+    .rsplit_once(':')
+    .map_or(name, |(_, local)| local)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  const POM: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<!-- <version>0.0.0</version> -->
+<project xmlns="http://maven.apache.org/POM/4.0.0">
+  <groupId>com.example</groupId>
+  <version>1.0.0</version>
+  <description><![CDATA[<version>9.9.9</version>]]></description>
+  <dependencies>
+    <dependency>
+      <artifactId>a</artifactId>
+      <version>2.0.0</version>
+    </dependency>
+    <dependency>
+      <artifactId>b</artifactId>
+      <optional/>
+    </dependency>
+  </dependencies>
+</project>
+"#;
+
+  fn parse(contents: &str) -> XmlDocument {
+    XmlDocument::parse(contents.to_owned()).unwrap()
+  }
+
+  #[test]
+  fn skips_comments_and_cdata() {
+    let document = parse(POM);
+    let versions = document
+      .find_all(&["project", "version"])
+      .map(|it| document.text(it))
+      .collect::<Vec<_>>();
+
+    assert_eq!(versions, ["1.0.0"]);
+  }
+
+  #[test]
+  fn finds_children() {
+    let document = parse(POM);
+    let path = ["project", "dependencies", "dependency"];
+    let dependency = document.find_all(&path).nth(1).unwrap();
+    let names = document
+      .children(dependency)
+      .map(|it| it.path.last().unwrap().as_str())
+      .collect::<Vec<_>>();
+
+    assert_eq!(names, ["artifactId", "optional"]);
+    assert!(document.child(dependency, "version").is_none());
+  }
+
+  #[test]
+  fn replaces_only_the_text() {
+    let mut document = parse(POM);
+    document
+      .set_text(&["project", "version"], "1.1.0")
+      .unwrap();
+
+    let expected = POM.replace("<version>1.0.0</version>", "<version>1.1.0</version>");
+    assert_eq!(document.contents, expected);
+  }
+
+  #[test]
+  fn keeps_whitespace_around_the_text() {
+    let contents = "<a>\r\n  <b> 1.0.0 </b>\r\n</a>\r\n";
+    let mut document = parse(contents);
+    document.set_text(&["a", "b"], "1.0.1").unwrap();
+
+    assert_eq!(document.contents, "<a>\r\n  <b> 1.0.1 </b>\r\n</a>\r\n");
+  }
+
+  #[test]
+  fn skips_quoted_angle_brackets() {
+    let contents = "<a>\n  <b test='x > 1' value=\"<\">1.0.0</b>\n</a>\n";
+    let mut document = parse(contents);
+    document.set_text(&["a", "b"], "1.0.1").unwrap();
+
+    assert_eq!(document.contents, contents.replace("1.0.0", "1.0.1"));
+  }
+
+  #[test]
+  fn handles_self_closing_elements() {
+    let contents = r#"<Project><PackageReference Include="A" Version="1.0.0" /></Project>"#;
+    let mut document = parse(contents);
+    let element = document
+      .find(&["Project", "PackageReference"])
+      .cloned()
+      .unwrap();
+
+    assert!(element.inner.is_empty());
+    assert_eq!(document.attribute(&element, "Include"), Some("A"));
+
+    document
+      .set_attribute(&element, "Version", "1.2.0")
+      .unwrap();
+
+    let expected = contents.replace("1.0.0", "1.2.0");
+    assert_eq!(document.contents, expected);
+  }
+
+  #[test]
+  fn reads_quoted_attributes() {
+    let contents = "<a>\n  <b test='x > 1' value=\"1.0.0\">text</b>\n</a>\n";
+    let mut document = parse(contents);
+    let element = document.find(&["a", "b"]).cloned().unwrap();
+
+    assert_eq!(document.text(&element), "text");
+    assert_eq!(document.attribute(&element, "test"), Some("x > 1"));
+    assert_eq!(document.attribute(&element, "value"), Some("1.0.0"));
+    assert_eq!(document.attribute(&element, "missing"), None);
+
+    document
+      .set_attribute(&element, "value", "2.0.0")
+      .unwrap();
+
+    let expected = contents.replace("1.0.0", "2.0.0");
+    assert_eq!(document.contents, expected);
+  }
+
+  #[test]
+  fn inserts_after_elements() {
+    let contents = "<project>\n    <artifactId>a</artifactId>\n    <packaging/>\n</project>\n";
+    let mut document = parse(contents);
+
+    let element = document
+      .find(&["project", "artifactId"])
+      .cloned()
+      .unwrap();
+    document
+      .insert_after(&element, "version", "1.0.0")
+      .unwrap();
+
+    let element = document
+      .find(&["project", "packaging"])
+      .cloned()
+      .unwrap();
+    document
+      .insert_after(&element, "name", "a & b")
+      .unwrap();
+
+    let expected = "\
+<project>
+    <artifactId>a</artifactId>
+    <version>1.0.0</version>
+    <packaging/>
+    <name>a &amp; b</name>
+</project>
+";
+    assert_eq!(document.contents, expected);
+  }
+
+  #[test]
+  fn ignores_namespaces() {
+    let contents = "<m:project xmlns:m=\"urn:m\"><m:version>1</m:version></m:project>";
+    let document = parse(contents);
+    let element = document.find(&["project", "version"]).unwrap();
+
+    assert_eq!(document.text(element), "1");
+  }
+
+  #[test]
+  fn fails_on_mismatched_tags() {
+    assert!(XmlDocument::parse("<a><b></a></b>".to_owned()).is_err());
+    assert!(XmlDocument::parse("<a><!-- </a>".to_owned()).is_err());
+  }
+}
@@ -0,0 +1,285 @@
+use anyhow::{Result, bail};
+use std::fs;
+use std::ops::Range;
+use std::path::Path;
+
+/// A YAML file that is edited line by line, so comments, quotes and
+/// indentation are kept as they are. Only block collections are supported.
+pub(super) struct YamlDocument {
+  contents: String,
+}
+
+impl YamlDocument {
+  pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+    let contents = fs::read_to_string(path)?;
+    Ok(Self { contents })
+  }
+
+  pub fn write<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+    fs::write(path, &self.contents).map_err(Into::into)
+  }
+
+  /// Replaces the scalar at `path`, keeping its quoting style when possible.
+  pub fn set_string(&mut self, path: &[&str], value: &str) -> Result<()> {
+    let Some(range) = self.find(path) else {
+      bail!("missing yaml key: {}", path.join("."));
+    };
+
+    let value = match self.contents[range.clone()].chars().next() {
+      Some('\'') => format!("'{}'", value.replace('\'', "''")),
+      Some('"') => serde_json::to_string(value)?,
+      _ if needs_quotes(value) => format!("'{}'", value.replace('\'', "''")),
+      _ => value.to_owned(),
+    };
+
+    self.contents.replace_range(range, &value);
+
+    Ok(())
+  }
+
+  /// Finds the span of the scalar value at `path`, where items of block
+  /// sequences are addressed by their index, e.g. `dependencies.0.version`.
+  fn find(&self, path: &[&str]) -> Option<Range<usize>> {
+    let mut stack: Vec<Node> = Vec::new();
+    let mut offset = 0;
+
+    for line in self.contents.split_inclusive('\n') {
+      let start = offset;
+      offset += line.len();
+
+      let line = line.trim_end_matches(['\n', '\r']);
+      let mut content = line.trim_start_matches(' ');
+      let mut indent = line.len() - content.len();
+
+      if content.is_empty() || content.starts_with('#') || content.starts_with("---") {
+        continue;
+      }
+
+      if let Some(item) = sequence_item(content) {
+        // Items may share the indentation of their parent key, but never of a previous item.
+        while stack
+          .last()
+          .is_some_and(|it| it.indent > indent || (it.indent == indent && it.is_item))
+        {
+          stack.pop();
+        }
+
+        let index = stack.last_mut().map_or(0, |parent| {
+          parent.items += 1;
+          parent.items - 1
+        });
+
+        stack.push(Node::item(indent, index));
+
+        indent += content.len() - item.len();
+        content = item;
+
+        if content.is_empty() || content.starts_with('#') {
+          continue;
+        }
+      }
+
+      let Some((key, rest)) = split_key(content) else {
+        continue;
+      };
+
+      while stack.last().is_some_and(|it| it.indent >= indent) {
+        stack.pop();
+      }
+
+      stack.push(Node::key(indent, key));
+
+      let matches = stack.len() == path.len()
+        && stack
+          .iter()
+          .zip(path)
+          .all(|(node, segment)| node.key == *segment);
+
+      if matches {
+        let value_start = start + line.len() - rest.trim_start().len();
+        let value = scalar(rest.trim_start())?;
+        return Some(value_start..value_start + value.len());
+      }
+    }
+
+    None
+  }
+}
+
+/// A mapping key or sequence item on the path to the current line.
+struct Node {
+  indent: usize,
+  key: String,
+  is_item: bool,
+  /// Number of sequence items found under this node so far.
+  items: usize,
+}
+
+impl Node {
+  fn key(indent: usize, key: String) -> Self {
+    Self {
+      indent,
+      key,
+      is_item: false,
+      items: 0,
+    }
+  }
+
+  fn item(indent: usize, index: usize) -> Self {
+    Self {
+      indent,
+      key: index.to_string(),
+      is_item: true,
+      items: 0,
+    }
+  }
+}
+
+/// Returns what follows the `- ` of a block sequence item.
+fn sequence_item(content: &str) -> Option<&str> {
+  if content == "-" {
+    return Some("");
+  }
+
+  let rest = content.strip_prefix('-')?;
+  let item = rest.trim_start_matches(' ');
+  (item.len() < rest.len()).then_some(item)
+}
+
+/// Splits `key: rest`, unquoting the key if needed.
+fn split_key(content: &str) -> Option<(String, &str)> {
+  if let Some(quote @ ('\'' | '"')) = content.chars().next() {
+    let end = content[1..].find(quote)? + 1;
+    let rest = content[end + 1..].strip_prefix(':')?;
+    return Some((content[1..end].to_owned(), rest));
+  }
+
+  let colon = content
+    .match_indices(':')
+    .map(|(i, _)| i)
+    .find(|&i| matches!(content.as_bytes().get(i + 1), None | Some(b' ' | b'\t')))?;
+
+  let key = content[..colon].trim_end();
+  Some((key.to_owned(), &content[colon + 1..]))
+}
+
+/// Returns the scalar at the start of `value`, without any trailing comment.
+fn scalar(value: &str) -> Option<&str> {
+  match value.chars().next() {
+    None | Some('|' | '>' | '{' | '[' | '&' | '*' | '#') => None,
+    Some(quote @ ('\'' | '"')) => {
+      let end = value[1..].find(quote)? + 1;
+      Some(&value[..=end])
+    }
+    Some(_) => {
+      let end = value.find(" #").unwrap_or(value.len());
+      Some(value[..end].trim_end())
+    }
+  }
+}
+
+fn needs_quotes(value: &str) -> bool {
+  value.starts_with([
+    '>', '|', '*', '&', '!', '%', '@', '`', '{', '[', ',', '#', '?', '-', ':',
+  ]) || value.contains(": ")
+    || value.contains(" #")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sets_scalars_in_place() {
+    let cases: &[(&str, &[&str], &str, &str)] = &[
+      (
+        "# chart\nversion: 1.0.0 # bumped by miho\nname: a\n",
+        &["version"],
+        "1.0.1",
+        "# chart\nversion: 1.0.1 # bumped by miho\nname: a\n",
+      ),
+      (
+        "a: '1.0.0'\nb: 1.0.0\n",
+        &["a"],
+        "2.0.0",
+        "a: '2.0.0'\nb: 1.0.0\n",
+      ),
+      ("a: \"1.0.0\"\n", &["a"], "it's", "a: \"it's\"\n"),
+      ("a: 'x'\n", &["a"], "it's", "a: 'it''s'\n"),
+      (
+        "catalog:\n  vue: 3.4.0\n",
+        &["catalog", "vue"],
+        ">=3.5.0",
+        "catalog:\n  vue: '>=3.5.0'\n",
+      ),
+      (
+        "name: a\r\nversion: 1.0.0\r\n",
+        &["version"],
+        "1.1.0",
+        "name: a\r\nversion: 1.1.0\r\n",
+      ),
+      (
+        "catalogs:\n  # legacy\n  vue2:\n    vue: ^2.7.0\n    '@vue/test-utils': ^1.3.0\nvue: ^3.0.0\n",
+        &["catalogs", "vue2", "@vue/test-utils"],
+        "^1.3.6",
+        "catalogs:\n  # legacy\n  vue2:\n    vue: ^2.7.0\n    '@vue/test-utils': ^1.3.6\nvue: ^3.0.0\n",
+      ),
+    ];
+
+    for (contents, path, value, expected) in cases {
+      let mut document = YamlDocument { contents: (*contents).to_owned() };
+      document.set_string(path, value).unwrap();
+      assert_eq!(document.contents, *expected, "{}", path.join("."));
+    }
+  }
+
+  #[test]
+  fn skips_blocks_and_comments() {
+    let contents = "script: |\n  version: 0.0.0\n# version: 0.0.0\nversion: 1.0.0\n";
+    let mut document = YamlDocument { contents: contents.to_owned() };
+    document
+      .set_string(&["version"], "1.0.1")
+      .unwrap();
+
+    assert_eq!(document.contents, contents.replace("1.0.0", "1.0.1"));
+  }
+
+  #[test]
+  fn fails_on_missing_or_nested_values() {
+    let contents = "name: a\ncatalog:\n  vue: ^3.0.0\n";
+    let mut document = YamlDocument { contents: contents.to_owned() };
+
+    assert!(
+      document
+        .set_string(&["version"], "1.0.0")
+        .is_err()
+    );
+    assert!(
+      document
+        .set_string(&["catalog"], "1.0.0")
+        .is_err()
+    );
+    assert_eq!(document.contents, contents);
+  }
+
+  #[test]
+  fn finds_sequence_items() {
+    let contents = "\
+dependencies:
+- name: redis
+  version: 17.0.0
+  repository: https://charts.bitnami.com/bitnami
+-   name: postgresql
+    version: \"12.0.0\"
+";
+    let mut document = YamlDocument { contents: contents.to_owned() };
+    document
+      .set_string(&["dependencies", "1", "version"], "12.1.0")
+      .unwrap();
+
+    assert_eq!(
+      document.contents,
+      contents.replace("\"12.0.0\"", "\"12.1.0\"")
+    );
+  }
+}
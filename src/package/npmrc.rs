@@ -0,0 +1,265 @@
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::{env, fs};
+
+use crate::registry::Token;
+
+const NPMRC: &str = ".npmrc";
+const DEFAULT_REGISTRY: &str = "https://registry.npmjs.org/";
+
+/// Settings from the `.npmrc` files npm would read at a directory.
+///
+/// <https://docs.npmjs.com/cli/configuring-npm/npmrc>
+#[derive(Debug, Default)]
+pub struct Npmrc {
+  values: HashMap<String, String>,
+}
+
+impl Npmrc {
+  /// Reads the global, user and project files, the closest one to `dir` taking precedence.
+  pub fn read(dir: &Path) -> Result<Self> {
+    let mut paths = Vec::new();
+
+    if let Some(path) = global_config() {
+      paths.push(path);
+    }
+
+    if let Some(path) = user_config() {
+      paths.push(path);
+    }
+
+    let mut project = dir
+      .ancestors()
+      .map(|it| it.join(NPMRC))
+      .collect::<Vec<_>>();
+
+    project.reverse();
+    paths.extend(project);
+
+    let mut npmrc = Self::default();
+    for path in paths {
+      if path.is_file() {
+        let contents = fs::read_to_string(&path)?;
+        npmrc.parse(&contents);
+      }
+    }
+
+    Ok(npmrc)
+  }
+
+  fn parse(&mut self, contents: &str) {
+    for line in contents.lines() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with(['#', ';', '[']) {
+        continue;
+      }
+
+      if let Some((key, value)) = line.split_once('=') {
+        let key = interpolate(key.trim());
+        let value = interpolate(unquote(value.trim()));
+        self.values.insert(key, value);
+      }
+    }
+  }
+
+  /// Registry a package is published to, which depends on its scope.
+  pub fn registry(&self, name: &str) -> String {
+    let scoped = name
+      .strip_prefix('@')
+      .and_then(|it| it.split_once('/'))
+      .and_then(|(scope, _)| self.get(&format!("@{scope}:registry")));
+
+    let registry = scoped
+      .or_else(|| env::var("npm_config_registry").ok())
+      .or_else(|| env::var("NPM_CONFIG_REGISTRY").ok())
+      .or_else(|| self.get("registry"))
+      .unwrap_or_else(|| DEFAULT_REGISTRY.to_owned());
+
+    registry.trim_end_matches('/').to_owned()
+  }
+
+  /// Value of the `Authorization` header for `registry`, from the credentials configured
+  /// for it or for any of its parent paths.
+  pub fn auth(&self, registry: &str) -> Option<Token> {
+    let mut key = format!("{}/", nerf_dart(registry));
+
+    loop {
+      if let Some(token) = self.get(&format!("{key}:_authToken")) {
+        return Some(Token::new(format!("Bearer {token}")));
+      }
+
+      if let Some(auth) = self.get(&format!("{key}:_auth")) {
+        return Some(Token::new(format!("Basic {auth}")));
+      }
+
+      // From `//host/path/` to `//host/`, which is as far as it goes.
+      let parent = key[..key.len() - 1].rfind('/')?;
+      if parent < 2 {
+        return None;
+      }
+
+      key.truncate(parent + 1);
+    }
+  }
+
+  fn get(&self, key: &str) -> Option<String> {
+    self
+      .values
+      .get(key)
+      .filter(|it| !it.is_empty())
+      .cloned()
+  }
+}
+
+/// `~/.npmrc`, unless `NPM_CONFIG_USERCONFIG` points elsewhere.
+fn user_config() -> Option<PathBuf> {
+  env::var_os("NPM_CONFIG_USERCONFIG")
+    .or_else(|| env::var_os("npm_config_userconfig"))
+    .map(PathBuf::from)
+    .or_else(|| env::home_dir().map(|it| it.join(NPMRC)))
+}
+
+/// `$PREFIX/etc/npmrc`, unless `NPM_CONFIG_GLOBALCONFIG` points elsewhere.
+fn global_config() -> Option<PathBuf> {
+  if let Some(path) =
+    env::var_os("NPM_CONFIG_GLOBALCONFIG").or_else(|| env::var_os("npm_config_globalconfig"))
+  {
+    return Some(PathBuf::from(path));
+  }
+
+  env::var_os("NPM_CONFIG_PREFIX")
+    .or_else(|| env::var_os("npm_config_prefix"))
+    .map(|it| PathBuf::from(it).join("etc").join("npmrc"))
+}
+
+/// Replaces `${VAR}` with the value of the environment variable, or with nothing for `${VAR?}`.
+/// Variables that are not set are otherwise left as they are.
+fn interpolate(value: &str) -> String {
+  let mut output = String::with_capacity(value.len());
+  let mut rest = value;
+
+  while let Some(start) = rest.find("${") {
+    let Some(end) = rest[start..].find('}') else {
+      break;
+    };
+    let end = start + end;
+    let name = &rest[start + 2..end];
+
+    output.push_str(&rest[..start]);
+    match (name.strip_suffix('?'), env::var(name.trim_end_matches('?'))) {
+      (_, Ok(value)) => output.push_str(&value),
+      (Some(_), Err(_)) => {}
+      (None, Err(_)) => output.push_str(&rest[start..=end]),
+    }
+
+    rest = &rest[end + 1..];
+  }
+
+  output.push_str(rest);
+  output
+}
+
+fn unquote(value: &str) -> &str {
+  ['"', '\'']
+    .into_iter()
+    .find_map(|quote| {
+      value
+        .strip_prefix(quote)
+        .and_then(|it| it.strip_suffix(quote))
+    })
+    .unwrap_or(value)
+}
+
+/// Registry URL without its protocol, as credentials are keyed in `.npmrc`, e.g. `//registry.npmjs.org`.
+fn nerf_dart(registry: &str) -> String {
+  let registry = registry.trim_end_matches('/');
+  let rest = registry
+    .split_once("//")
+    .map_or(registry, |(_, rest)| rest);
+
+  format!("//{rest}")
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn parse(contents: &str) -> Npmrc {
+    let mut npmrc = Npmrc::default();
+    npmrc.parse(contents);
+    npmrc
+  }
+
+  #[test]
+  fn interpolates_variables() {
+    let path = env::var("PATH").unwrap();
+    assert_eq!(interpolate("${PATH}/bin"), format!("{path}/bin"));
+    assert_eq!(interpolate("a${MIHO_UNSET_VARIABLE?}b"), "ab");
+    assert_eq!(
+      interpolate("${MIHO_UNSET_VARIABLE}"),
+      "${MIHO_UNSET_VARIABLE}"
+    );
+    assert_eq!(interpolate("${PATH"), "${PATH");
+  }
+
+  #[test]
+  fn parses_values() {
+    let npmrc = parse(
+      "# comment\r\n; comment\r\n[section]\r\n@a:registry = \"https://npm.a.com/\"\r\nempty=\r\n",
+    );
+
+    assert_eq!(npmrc.registry("@a/b"), "https://npm.a.com");
+    assert_eq!(npmrc.get("empty"), None);
+    assert_eq!(npmrc.get("[section]"), None);
+  }
+
+  #[test]
+  fn finds_tokens_by_nerf_dart() {
+    let npmrc = parse(
+      "\
+//npm.a.com/:_authToken=root
+//npm.a.com/team/:_auth=dGVhbQ==
+//npm.b.com/nested/path/:_authToken=nested
+",
+    );
+
+    let auth = |registry| {
+      npmrc
+        .auth(registry)
+        .map(|it| it.as_str().to_owned())
+    };
+    assert_eq!(auth("https://npm.a.com"), Some("Bearer root".to_owned()));
+    assert_eq!(
+      auth("https://npm.a.com/other/"),
+      Some("Bearer root".to_owned())
+    );
+    assert_eq!(
+      auth("https://npm.a.com/team"),
+      Some("Basic dGVhbQ==".to_owned())
+    );
+    assert_eq!(
+      auth("https://npm.a.com/team/sub"),
+      Some("Basic dGVhbQ==".to_owned())
+    );
+    assert_eq!(
+      auth("https://npm.b.com/nested/path"),
+      Some("Bearer nested".to_owned())
+    );
+    assert_eq!(auth("https://npm.b.com/nested"), None);
+    assert_eq!(auth("https://npm.c.com"), None);
+  }
+
+  #[test]
+  fn strips_the_protocol() {
+    assert_eq!(
+      nerf_dart("https://registry.npmjs.org/"),
+      "//registry.npmjs.org"
+    );
+    assert_eq!(
+      nerf_dart("http://localhost:4873/npm"),
+      "//localhost:4873/npm"
+    );
+    assert_eq!(nerf_dart("//npm.a.com"), "//npm.a.com");
+  }
+}
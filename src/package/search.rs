@@ -1,10 +1,12 @@
 use anyhow::{Result, bail};
-use globset::{Glob, GlobSet, GlobSetBuilder};
+use colored::Colorize;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
 use ignore::{DirEntry, WalkBuilder};
 use itertools::Itertools;
 use std::path::Path;
 
 use crate::agent::Agent;
+use crate::config::Config;
 use crate::package::Package;
 use crate::package::manifest::ManifestKind;
 
@@ -13,6 +15,7 @@ pub struct SearchBuilder<'a> {
   path: Vec<&'a Path>,
   packages: Vec<&'a str>,
   agents: Vec<Agent>,
+  workspace_only: bool,
 }
 
 impl<'a> SearchBuilder<'a> {
@@ -21,6 +24,7 @@ impl<'a> SearchBuilder<'a> {
       path: path.to_vec(),
       packages: Vec::new(),
       agents: Vec::new(),
+      workspace_only: false,
     }
   }
 
@@ -40,6 +44,12 @@ impl<'a> SearchBuilder<'a> {
     self
   }
 
+  /// Only include workspace roots and their members.
+  pub fn workspace_only(mut self) -> Self {
+    self.workspace_only = true;
+    self
+  }
+
   pub fn search(self) -> Result<Vec<Package>> {
     let Some((first, other)) = self.path.split_first() else {
       return Ok(Vec::new());
@@ -63,6 +73,14 @@ impl<'a> SearchBuilder<'a> {
       }
     }
 
+    for path in &self.path {
+      for package in version_files(path)? {
+        if !packages.contains(&package) {
+          packages.push(package);
+        }
+      }
+    }
+
     if !self.packages.is_empty() {
       packages.retain(|it| self.packages.contains(&it.name.as_str()));
     }
@@ -71,6 +89,10 @@ impl<'a> SearchBuilder<'a> {
       packages.retain(|it| self.agents.contains(&it.manifest.agent()));
     }
 
+    if self.workspace_only {
+      packages.retain(|it| it.workspace().is_some());
+    }
+
     if packages.is_empty() {
       bail!("no valid package found");
     }
@@ -92,12 +114,60 @@ fn build_globset() -> Result<GlobSet> {
   }
 
   add!(CargoToml);
+  add!(ChartYaml);
+  add!(ComposerJson);
+  add!(Csproj);
+  add!(DenoJson);
+  add!(DirectoryBuildProps);
+  add!(GoMod);
+  add!(GradleProperties);
+  add!(ManifestJson);
   add!(PackageJson);
+  add!(PnpmWorkspaceYaml);
+  add!(PomXml);
+  add!(PubspecYaml);
+  add!(PyprojectToml);
   add!(TauriConfJson);
 
   builder.build().map_err(Into::into)
 }
 
+/// Finds the version files configured in the `miho.toml` at `dir`.
+fn version_files(dir: &Path) -> Result<Vec<Package>> {
+  let dir = dir.canonicalize()?;
+  let config = Config::read(&dir)?;
+  let mut packages = Vec::new();
+
+  for version_file in &config.version_file {
+    // As in `.gitignore`, `*` does not match across directories, which `**` is for.
+    let glob = GlobBuilder::new(&version_file.path)
+      .literal_separator(true)
+      .build()?
+      .compile_matcher();
+    for entry in WalkBuilder::new(&dir).build().flatten() {
+      let path = entry.path();
+      let relative = path.strip_prefix(&dir).unwrap_or(path);
+      if !glob.is_match(relative) || !path.is_file() {
+        continue;
+      }
+
+      // A file the entry cannot read should not keep the other packages from being found.
+      match Package::from_version_file(path, version_file, &dir) {
+        Ok(package) => packages.push(package),
+        Err(err) => {
+          let warning = format!("skipped version file {}:", relative.display()).bright_yellow();
+          println!(
+            "{warning} {err} (from `{}` in miho.toml)",
+            version_file.path
+          );
+        }
+      }
+    }
+  }
+
+  Ok(packages)
+}
+
 fn is_match(glob: &GlobSet, entry: &DirEntry) -> bool {
   if !glob.is_match(entry.path()) {
     return false;
@@ -105,3 +175,24 @@ fn is_match(glob: &GlobSet, entry: &DirEntry) -> bool {
 
   matches!(entry.file_type(), Some(it) if !it.is_dir())
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+
+  #[test]
+  fn matches_version_files_within_directories() {
+    let dir = TempDir::new();
+    dir.write("miho.toml", "[[version-file]]\npath = \"*.txt\"\n");
+    dir.write("VERSION.txt", "1.2.3\n");
+    dir.write("nested/VERSION.txt", "4.5.6\n");
+
+    let packages = version_files(dir.path()).unwrap();
+    let [package] = packages.as_slice() else {
+      panic!("expected one version file");
+    };
+
+    assert_eq!(package.path, dir.path().join("VERSION.txt"));
+  }
+}
@@ -0,0 +1,143 @@
+use anyhow::Result;
+use globset::{Glob, GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::agent::Agent;
+
+const PNPM_WORKSPACE: &str = "pnpm-workspace.yaml";
+const PACKAGE_JSON: &str = "package.json";
+
+/// A Node workspace, declared either in `pnpm-workspace.yaml` or
+/// in the `workspaces` field of the root `package.json`.
+#[derive(Debug)]
+pub struct NodeWorkspace {
+  pub root: PathBuf,
+  pub agent: Option<Agent>,
+  members: GlobSet,
+  excluded: GlobSet,
+}
+
+/// <https://pnpm.io/pnpm-workspace_yaml>
+#[derive(Deserialize)]
+struct PnpmWorkspace {
+  #[serde(default)]
+  packages: Vec<String>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all(deserialize = "camelCase"))]
+struct RootPackageJson {
+  workspaces: Option<Workspaces>,
+  package_manager: Option<String>,
+}
+
+/// <https://docs.npmjs.com/cli/using-npm/workspaces>
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Workspaces {
+  List(Vec<String>),
+  Object {
+    #[serde(default)]
+    packages: Vec<String>,
+  },
+}
+
+impl NodeWorkspace {
+  /// Finds the workspace that owns the `package.json` at `path`, if any.
+  pub fn find(path: &Path) -> Result<Option<Self>> {
+    let Some(dir) = path.parent() else {
+      return Ok(None);
+    };
+
+    for ancestor in dir.ancestors() {
+      if let Some(workspace) = Self::read(ancestor)?
+        && (ancestor == dir || workspace.contains(dir))
+      {
+        return Ok(Some(workspace));
+      }
+    }
+
+    Ok(None)
+  }
+
+  /// Reads the workspace declared at `dir`, if there is one.
+  pub fn read(dir: &Path) -> Result<Option<Self>> {
+    let package_json = dir.join(PACKAGE_JSON);
+    let root = if let Ok(true) = package_json.try_exists() {
+      let contents = fs::read_to_string(&package_json)?;
+      serde_json::from_str::<RootPackageJson>(contents.trim_start_matches('\u{feff}')).ok()
+    } else {
+      None
+    };
+
+    let agent = root
+      .as_ref()
+      .and_then(|it| it.package_manager.as_deref())
+      .and_then(Agent::from_package_manager);
+
+    let pnpm_workspace = dir.join(PNPM_WORKSPACE);
+    let (patterns, agent) = if let Ok(true) = pnpm_workspace.try_exists() {
+      let contents = fs::read_to_string(&pnpm_workspace)?;
+      let workspace: Option<PnpmWorkspace> = serde_yaml::from_str(&contents)?;
+      let patterns = workspace
+        .map(|it| it.packages)
+        .unwrap_or_default();
+      (patterns, agent.or(Some(Agent::Pnpm)))
+    } else {
+      match root.and_then(|it| it.workspaces) {
+        Some(Workspaces::List(packages) | Workspaces::Object { packages }) => (packages, agent),
+        None => return Ok(None),
+      }
+    };
+
+    let agent = agent.or_else(|| Agent::from_lockfile(dir));
+    Self::new(dir, agent, &patterns).map(Some)
+  }
+
+  fn new(dir: &Path, agent: Option<Agent>, patterns: &[String]) -> Result<Self> {
+    let mut members = GlobSetBuilder::new();
+    let mut excluded = GlobSetBuilder::new();
+
+    for pattern in patterns {
+      let (builder, pattern) = match pattern.strip_prefix('!') {
+        Some(pattern) => (&mut excluded, pattern),
+        None => (&mut members, pattern.as_str()),
+      };
+
+      let pattern = pattern
+        .trim_start_matches("./")
+        .trim_end_matches('/');
+
+      builder.add(glob(pattern)?);
+    }
+
+    Ok(Self {
+      root: dir.to_path_buf(),
+      agent,
+      members: members.build()?,
+      excluded: excluded.build()?,
+    })
+  }
+
+  /// Whether the package at `dir` is the root or one of the members of this workspace.
+  pub fn contains(&self, dir: &Path) -> bool {
+    if dir == self.root {
+      return true;
+    }
+
+    let Ok(relative) = dir.strip_prefix(&self.root) else {
+      return false;
+    };
+
+    self.members.is_match(relative) && !self.excluded.is_match(relative)
+  }
+}
+
+fn glob(pattern: &str) -> Result<Glob> {
+  GlobBuilder::new(pattern)
+    .literal_separator(true)
+    .build()
+    .map_err(Into::into)
+}
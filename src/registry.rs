@@ -0,0 +1,161 @@
+mod cache;
+mod go_proxy;
+mod helm;
+mod jsr;
+mod local;
+mod maven;
+mod npm;
+mod nuget;
+mod packagist;
+mod pub_dev;
+mod pypi;
+mod sparse_index;
+
+use anyhow::{Result, anyhow};
+use cache::send;
+use reqwest::Client;
+use semver::Version;
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::{Arc, LazyLock};
+use strum::IntoEnumIterator;
+
+pub use cache::{DiskCache, NotCached};
+pub use go_proxy::GoProxy;
+pub use helm::Helm;
+pub use jsr::Jsr;
+pub use local::LocalRegistry;
+pub use maven::Maven;
+pub use npm::Npm;
+pub use nuget::NuGet;
+pub use packagist::Packagist;
+pub use pub_dev::PubDev;
+pub use pypi::PyPi;
+pub use sparse_index::SparseIndex;
+
+use crate::agent::Agent;
+use crate::dependency::Dependency;
+
+const USER_AGENT: &str = concat!("miho/", env!("CARGO_PKG_VERSION"));
+
+static HTTP_CLIENT: LazyLock<Client> = LazyLock::new(|| {
+  Client::builder()
+    .use_rustls_tls()
+    .user_agent(USER_AGENT)
+    .brotli(true)
+    .gzip(true)
+    .build()
+    .expect("failed to create http client")
+});
+
+pub type RegistryFuture<'a> = Pin<Box<dyn Future<Output = Result<Vec<Version>>> + Send + 'a>>;
+
+/// Where the versions of a dependency are published.
+pub trait Registry: Send + Sync {
+  /// Versions of `dependency` it may be updated to, leaving out yanked or deprecated ones.
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a>;
+}
+
+/// Registry used by each agent to fetch the versions of its dependencies.
+#[derive(Clone)]
+pub struct Registries {
+  registries: HashMap<Agent, Arc<dyn Registry>>,
+}
+
+impl Registries {
+  /// A set without any registry, to be filled with [`Registries::with`].
+  pub fn empty() -> Self {
+    Self { registries: HashMap::new() }
+  }
+
+  /// Uses `registry` for the dependencies of `agent`.
+  #[must_use]
+  pub fn with(mut self, agent: Agent, registry: impl Registry + 'static) -> Self {
+    self.registries.insert(agent, Arc::new(registry));
+    self
+  }
+
+  /// Reads every registry from the files at `dir`, with one directory per ecosystem,
+  /// e.g. `cargo/serde.json` or `npm/@types/node.json`.
+  pub fn local(dir: &Path) -> Self {
+    let mut registries = Self::empty();
+    for agent in Agent::iter() {
+      let name = if agent.is_node() {
+        Agent::Npm.as_ref().to_lowercase()
+      } else {
+        agent.as_ref().to_lowercase()
+      };
+
+      registries = registries.with(agent, LocalRegistry::new(dir.join(name)));
+    }
+
+    registries
+  }
+
+  pub fn get(&self, agent: Agent) -> Result<Arc<dyn Registry>> {
+    self
+      .registries
+      .get(&agent)
+      .map(Arc::clone)
+      .ok_or_else(|| anyhow!("no registry for {agent}"))
+  }
+}
+
+/// The public registry of each ecosystem.
+impl Default for Registries {
+  fn default() -> Self {
+    Self::empty()
+      .with(Agent::Bun, Npm::default())
+      .with(Agent::Cargo, SparseIndex::default())
+      .with(Agent::Composer, Packagist::default())
+      .with(Agent::Dart, PubDev::default())
+      .with(Agent::Deno, Jsr::default())
+      .with(Agent::Dotnet, NuGet::default())
+      .with(Agent::Go, GoProxy)
+      .with(Agent::Gradle, Maven::default())
+      .with(Agent::Helm, Helm)
+      .with(Agent::Maven, Maven::default())
+      .with(Agent::Npm, Npm::default())
+      .with(Agent::Pnpm, Npm::default())
+      .with(Agent::Python, PyPi::default())
+      .with(Agent::Yarn, Npm::default())
+  }
+}
+
+impl fmt::Debug for Registries {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.debug_set()
+      .entries(self.registries.keys())
+      .finish()
+  }
+}
+
+/// A secret sent to authenticate with a registry, which debug output leaves out.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Token(String);
+
+impl Token {
+  pub fn new(token: impl Into<String>) -> Self {
+    Self(token.into())
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl fmt::Debug for Token {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str("Token(***)")
+  }
+}
+
+/// Reads the base URL of a registry from `var`, falling back to `default`.
+fn base_url(var: &str, default: &str) -> String {
+  std::env::var(var)
+    .unwrap_or_else(|_| default.to_owned())
+    .trim_end_matches('/')
+    .to_owned()
+}
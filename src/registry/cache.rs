@@ -0,0 +1,485 @@
+use anyhow::Result;
+use reqwest::header::{
+  AUTHORIZATION, ETAG, HeaderMap, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED,
+};
+use reqwest::{Request, RequestBuilder, StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::{env, error, fmt, io};
+
+use super::HTTP_CLIENT;
+
+/// Overrides where the responses of the registries are kept.
+const CACHE_DIR_ENV: &str = "MIHO_CACHE_DIR";
+
+/// Most file systems do not allow longer file names, in bytes.
+const MAX_FILE_NAME: usize = 255;
+
+static CACHE: OnceLock<DiskCache> = OnceLock::new();
+
+/// Responses of the registries, kept on disk so that other runs can reuse them.
+#[derive(Debug)]
+pub struct DiskCache {
+  dir: PathBuf,
+  ttl: Duration,
+  offline: bool,
+}
+
+/// A response stored on disk, with what is needed to revalidate it.
+#[derive(Deserialize, Serialize)]
+struct Entry {
+  url: String,
+  etag: Option<String>,
+  last_modified: Option<String>,
+  /// Seconds since the Unix epoch when the registry last confirmed the response.
+  fetched_at: u64,
+  body: String,
+}
+
+/// Returned when offline and nothing is cached for a request.
+#[derive(Debug)]
+pub struct NotCached {
+  pub url: String,
+}
+
+impl DiskCache {
+  /// Cache at the user cache directory, whose responses are fresh for `ttl`.
+  /// When `offline`, every response comes from it, however old.
+  pub fn new(ttl: Duration, offline: bool) -> Option<Self> {
+    let dir = env::var_os(CACHE_DIR_ENV)
+      .map(PathBuf::from)
+      .or_else(|| user_cache_dir().map(|it| it.join("miho")))?;
+
+    Some(Self { dir, ttl, offline })
+  }
+
+  /// Uses `self` for every request to the registries from now on.
+  pub fn install(self) {
+    CACHE
+      .set(self)
+      .expect("cache is already installed");
+  }
+
+  /// Registries get a directory of their own, in which the path and query of `url` name the entry.
+  /// Responses to a token are kept apart from those to other tokens, or to no token at all.
+  fn path(&self, url: &str, token: Option<&str>) -> PathBuf {
+    let (mut host, name) = match Url::parse(url) {
+      Ok(url) => {
+        let host = match url.port() {
+          Some(port) => format!("{}:{port}", url.host_str().unwrap_or_default()),
+          None => url.host_str().unwrap_or_default().to_owned(),
+        };
+
+        let path = match url.query() {
+          Some(query) => format!("{}?{query}", url.path()),
+          None => url.path().to_owned(),
+        };
+
+        (host, path)
+      }
+      Err(_) => (String::new(), url.to_owned()),
+    };
+
+    if let Some(token) = token {
+      host = format!("{:016x}@{host}", digest(token));
+    }
+
+    self
+      .dir
+      .join("registry")
+      .join(file_name(&host, ""))
+      .join(file_name(&name, ".json"))
+  }
+
+  async fn read(&self, url: &str, token: Option<&str>) -> Result<Option<Entry>> {
+    let contents = match tokio::fs::read_to_string(self.path(url, token)).await {
+      Ok(contents) => contents,
+      Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+      Err(err) => return Err(err.into()),
+    };
+
+    // Entries that cannot be read, or that belong to another url whose name was shortened
+    // to the same one, are fetched again.
+    let entry = serde_json::from_str::<Entry>(&contents)
+      .ok()
+      .filter(|it| it.url == url);
+
+    Ok(entry)
+  }
+
+  /// Writes to a temporary file first, so that other runs never read half of an entry.
+  async fn write(&self, entry: &Entry, token: Option<&str>) -> Result<()> {
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let path = self.path(&entry.url, token);
+    if let Some(parent) = path.parent() {
+      tokio::fs::create_dir_all(parent).await?;
+    }
+
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let temp = path.with_extension(format!("{}.{id}.tmp", std::process::id()));
+    tokio::fs::write(&temp, serde_json::to_vec(entry)?).await?;
+    tokio::fs::rename(&temp, &path).await?;
+
+    Ok(())
+  }
+
+  fn is_fresh(&self, entry: &Entry) -> bool {
+    now().saturating_sub(entry.fetched_at) < self.ttl.as_secs()
+  }
+
+  /// Sends `request`, unless a response is fresh in the cache, revalidating it otherwise.
+  async fn send(&self, mut request: Request) -> Result<String> {
+    let url = request.url().to_string();
+    let token = request
+      .headers()
+      .get(AUTHORIZATION)
+      .and_then(|it| it.to_str().ok())
+      .map(ToOwned::to_owned);
+    let token = token.as_deref();
+
+    let entry = self.read(&url, token).await?;
+    match entry {
+      Some(entry) if self.offline || self.is_fresh(&entry) => return Ok(entry.body),
+      None if self.offline => return Err(NotCached { url }.into()),
+      _ => {}
+    }
+
+    if let Some(entry) = &entry {
+      let headers = request.headers_mut();
+      if let Some(etag) = entry
+        .etag
+        .as_deref()
+        .and_then(|it| it.parse().ok())
+      {
+        headers.insert(IF_NONE_MATCH, etag);
+      }
+
+      if let Some(date) = entry
+        .last_modified
+        .as_deref()
+        .and_then(|it| it.parse().ok())
+      {
+        headers.insert(IF_MODIFIED_SINCE, date);
+      }
+    }
+
+    let response = HTTP_CLIENT.execute(request).await?;
+    if response.status() == StatusCode::NOT_MODIFIED
+      && let Some(mut entry) = entry
+    {
+      entry.fetched_at = now();
+      self.write(&entry, token).await?;
+      return Ok(entry.body);
+    }
+
+    let response = response.error_for_status()?;
+    let headers = response.headers().clone();
+    let body = response.text().await?;
+
+    let entry = Entry::new(url, &headers, body);
+    self.write(&entry, token).await?;
+
+    Ok(entry.body)
+  }
+}
+
+impl Entry {
+  fn new(url: String, headers: &HeaderMap, body: String) -> Self {
+    let header = |name| {
+      headers
+        .get(name)
+        .and_then(|it| it.to_str().ok())
+        .map(ToOwned::to_owned)
+    };
+
+    Self {
+      url,
+      etag: header(ETAG),
+      last_modified: header(LAST_MODIFIED),
+      fetched_at: now(),
+      body,
+    }
+  }
+}
+
+impl fmt::Display for NotCached {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "no cached response for {}", self.url)
+  }
+}
+
+impl error::Error for NotCached {}
+
+/// Sends `request` and returns the body of the response, which may come from the disk cache
+/// if it is still fresh or the registry answers that it has not changed since.
+pub async fn send(request: RequestBuilder) -> Result<String> {
+  let request = request.build()?;
+  if let Some(cache) = CACHE.get() {
+    return cache.send(request).await;
+  }
+
+  let response = HTTP_CLIENT.execute(request).await?;
+  Ok(response.error_for_status()?.text().await?)
+}
+
+/// Percent-encodes every byte of `value` that is not safe in a file name, `/` included,
+/// so that distinct urls never share an entry.
+fn escape(value: &str) -> String {
+  let mut output = String::with_capacity(value.len());
+  for byte in value.bytes() {
+    if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_' | b'.') {
+      output.push(char::from(byte));
+    } else {
+      let _ = write!(output, "%{byte:02X}");
+    }
+  }
+
+  output
+}
+
+/// Escapes `value` into a file name with `extension`, replacing the end of names that would
+/// be too long with a digest of the whole value.
+fn file_name(value: &str, extension: &str) -> String {
+  let mut name = escape(value);
+  if name.len() + extension.len() > MAX_FILE_NAME {
+    let digest = format!("~{:016x}", digest(value));
+    // Escaped names are ASCII, so any length is a char boundary.
+    name.truncate(MAX_FILE_NAME - extension.len() - digest.len());
+    name.push_str(&digest);
+  }
+
+  name.push_str(extension);
+  name
+}
+
+/// Digest of `value` that is only used to name files, so a new one after an upgrade of the
+/// standard library only misses what is cached.
+fn digest(value: &str) -> u64 {
+  let mut hasher = DefaultHasher::new();
+  value.hash(&mut hasher);
+  hasher.finish()
+}
+
+fn now() -> u64 {
+  SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|it| it.as_secs())
+    .unwrap_or_default()
+}
+
+/// <https://specifications.freedesktop.org/basedir-spec/latest/>
+#[cfg(all(unix, not(target_os = "macos")))]
+fn user_cache_dir() -> Option<PathBuf> {
+  env::var_os("XDG_CACHE_HOME")
+    .map(PathBuf::from)
+    .filter(|it| it.is_absolute())
+    .or_else(|| env::home_dir().map(|it| it.join(".cache")))
+}
+
+#[cfg(target_os = "macos")]
+fn user_cache_dir() -> Option<PathBuf> {
+  env::home_dir().map(|it| it.join("Library").join("Caches"))
+}
+
+#[cfg(windows)]
+fn user_cache_dir() -> Option<PathBuf> {
+  env::var_os("LOCALAPPDATA").map(PathBuf::from)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+  use std::path::Path;
+  use std::sync::{Arc, Mutex};
+  use tokio::io::{AsyncReadExt, AsyncWriteExt};
+  use tokio::net::TcpListener;
+
+  fn cache(dir: &Path, ttl: u64) -> DiskCache {
+    DiskCache {
+      dir: dir.to_path_buf(),
+      ttl: Duration::from_secs(ttl),
+      offline: false,
+    }
+  }
+
+  #[test]
+  fn escapes_file_names() {
+    assert_eq!(escape("index.crates.io"), "index.crates.io");
+    assert_eq!(escape("/se/rd/serde"), "%2Fse%2Frd%2Fserde");
+    assert_eq!(escape("@scope/name?a=b"), "%40scope%2Fname%3Fa%3Db");
+    assert_eq!(escape("localhost:8080"), "localhost%3A8080");
+    assert_eq!(escape("ç"), "%C3%A7");
+  }
+
+  #[test]
+  fn names_entries_after_urls() {
+    let dir = TempDir::new();
+    let cache = cache(dir.path(), 0);
+    let registry = dir.path().join("registry");
+
+    assert_eq!(
+      cache.path("https://index.crates.io/se/rd/serde", None),
+      registry
+        .join("index.crates.io")
+        .join("%2Fse%2Frd%2Fserde.json")
+    );
+    assert_eq!(
+      cache.path("http://localhost:8080/v1?page=2", None),
+      registry
+        .join("localhost%3A8080")
+        .join("%2Fv1%3Fpage%3D2.json")
+    );
+  }
+
+  #[test]
+  fn keeps_responses_to_tokens_apart() {
+    let dir = TempDir::new();
+    let cache = cache(dir.path(), 0);
+    let url = "https://registry.example.com/package";
+
+    let anonymous = cache.path(url, None);
+    let alice = cache.path(url, Some("Bearer alice"));
+    let bob = cache.path(url, Some("Bearer bob"));
+
+    assert_ne!(anonymous, alice);
+    assert_ne!(alice, bob);
+    assert_eq!(alice, cache.path(url, Some("Bearer alice")));
+    assert!(!alice.to_string_lossy().contains("alice"));
+  }
+
+  #[test]
+  fn shortens_long_names() {
+    let dir = TempDir::new();
+    let cache = cache(dir.path(), 0);
+    let long = format!("https://example.com/{}", "a".repeat(300));
+
+    let a = cache.path(&format!("{long}/a"), None);
+    let b = cache.path(&format!("{long}/b"), None);
+    assert_ne!(a, b);
+
+    for path in [a, b] {
+      let name = path.file_name().unwrap().to_string_lossy();
+      assert_eq!(name.len(), MAX_FILE_NAME);
+      assert!(name.ends_with(".json"));
+    }
+  }
+
+  #[test]
+  fn expires_entries_after_the_ttl() {
+    let entry = |age| Entry {
+      url: String::new(),
+      etag: None,
+      last_modified: None,
+      fetched_at: now() - age,
+      body: String::new(),
+    };
+
+    let dir = TempDir::new();
+    let cache = cache(dir.path(), 600);
+    assert!(cache.is_fresh(&entry(0)));
+    assert!(cache.is_fresh(&entry(599)));
+    assert!(!cache.is_fresh(&entry(600)));
+  }
+
+  /// Serves `body` with an `ETag` and a `Last-Modified` date, answering `304 Not Modified`
+  /// to requests that send them back, and records the headers of every request.
+  async fn serve(body: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/package", listener.local_addr().unwrap());
+    let requests = Arc::new(Mutex::new(Vec::new()));
+
+    let recorded = Arc::clone(&requests);
+    tokio::spawn(async move {
+      loop {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buffer = [0; 1024];
+        while !request.ends_with(b"\r\n\r\n") {
+          let read = stream.read(&mut buffer).await.unwrap();
+          if read == 0 {
+            break;
+          }
+
+          request.extend_from_slice(&buffer[..read]);
+        }
+
+        let request = String::from_utf8_lossy(&request).to_lowercase();
+        let response = if request.contains("if-none-match: \"v1\"") {
+          "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_owned()
+        } else {
+          format!(
+            "HTTP/1.1 200 OK\r\nETag: \"v1\"\r\nLast-Modified: Wed, 21 Oct 2015 07:28:00 GMT\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+          )
+        };
+
+        recorded.lock().unwrap().push(request);
+        stream
+          .write_all(response.as_bytes())
+          .await
+          .unwrap();
+      }
+    });
+
+    (url, requests)
+  }
+
+  #[tokio::test]
+  async fn revalidates_stale_entries() {
+    let (url, requests) = serve(r#"{"versions":["1.0.0"]}"#).await;
+    let dir = TempDir::new();
+    let stale = cache(dir.path(), 0);
+
+    let body = stale
+      .send(HTTP_CLIENT.get(&url).build().unwrap())
+      .await
+      .unwrap();
+    assert_eq!(body, r#"{"versions":["1.0.0"]}"#);
+
+    let body = stale
+      .send(HTTP_CLIENT.get(&url).build().unwrap())
+      .await
+      .unwrap();
+    assert_eq!(body, r#"{"versions":["1.0.0"]}"#);
+
+    {
+      let requests = requests.lock().unwrap();
+      assert_eq!(requests.len(), 2);
+      assert!(!requests[0].contains("if-none-match"));
+      assert!(requests[1].contains("if-none-match: \"v1\""));
+      assert!(requests[1].contains("if-modified-since: wed, 21 oct 2015 07:28:00 gmt"));
+    }
+
+    // Fresh entries are used without asking the registry.
+    let fresh = cache(dir.path(), 600);
+    let body = fresh
+      .send(HTTP_CLIENT.get(&url).build().unwrap())
+      .await
+      .unwrap();
+    assert_eq!(body, r#"{"versions":["1.0.0"]}"#);
+    assert_eq!(requests.lock().unwrap().len(), 2);
+  }
+
+  #[tokio::test]
+  async fn fails_offline_without_an_entry() {
+    let dir = TempDir::new();
+    let cache = DiskCache {
+      offline: true,
+      ..cache(dir.path(), 0)
+    };
+
+    let request = HTTP_CLIENT
+      .get("http://127.0.0.1:9/package")
+      .build()
+      .unwrap();
+    let err = cache.send(request).await.unwrap_err();
+    assert!(err.is::<NotCached>());
+  }
+}
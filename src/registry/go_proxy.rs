@@ -0,0 +1,72 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use std::env;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, send};
+use crate::dependency::Dependency;
+
+const GO_PROXY: &str = "https://proxy.golang.org";
+
+/// <https://go.dev/ref/mod#goproxy-protocol>
+pub struct GoProxy;
+
+impl GoProxy {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/{}/@v/list", proxy()?, escape_module(name));
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    // Versions are listed one per line, and `+incompatible` ones belong to other major versions.
+    let versions = body
+      .lines()
+      .filter_map(|it| Version::parse(it.trim().strip_prefix('v')?).ok())
+      .filter(|it| it.build.is_empty())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Registry for GoProxy {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
+
+/// First proxy listed in `GOPROXY`, skipping the `direct` and `off` keywords.
+///
+/// <https://go.dev/ref/mod#environment-variables>
+fn proxy() -> Result<String> {
+  let Ok(value) = env::var("GOPROXY") else {
+    return Ok(GO_PROXY.to_owned());
+  };
+
+  let proxy = value
+    .split([',', '|'])
+    .map(str::trim)
+    .find(|it| !it.is_empty() && *it != "direct" && *it != "off");
+
+  match proxy {
+    Some(proxy) => Ok(proxy.trim_end_matches('/').to_owned()),
+    None if value.trim().is_empty() => Ok(GO_PROXY.to_owned()),
+    None => bail!("GOPROXY has no proxy to fetch modules from: {value}"),
+  }
+}
+
+/// Uppercase letters are escaped as `!` followed by the lowercase letter.
+///
+/// <https://go.dev/ref/mod#goproxy-protocol>
+fn escape_module(path: &str) -> String {
+  let mut escaped = String::with_capacity(path.len());
+  for char in path.chars() {
+    if char.is_ascii_uppercase() {
+      escaped.push('!');
+      escaped.push(char.to_ascii_lowercase());
+    } else {
+      escaped.push(char);
+    }
+  }
+
+  escaped
+}
@@ -0,0 +1,57 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde_yaml::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, send};
+use crate::dependency::Dependency;
+
+/// Chart repositories have no default, so each dependency carries the URL of its own.
+///
+/// <https://helm.sh/docs/topics/chart_repository/#the-index-file>
+pub struct Helm;
+
+impl Helm {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let Some(repository) = dependency.registry_url.as_deref() else {
+      bail!("no repository found for {name}");
+    };
+
+    let index = if let Some(dir) = repository.strip_prefix("file://") {
+      tokio::fs::read_to_string(format!("{dir}/index.yaml")).await?
+    } else {
+      let url = format!("{repository}/index.yaml");
+      send(HTTP_CLIENT.get(&url)).await?
+    };
+
+    let index: Value = serde_yaml::from_str(&index)?;
+    let Some(entries) = index
+      .get("entries")
+      .and_then(|it| it.get(name))
+      .and_then(Value::as_sequence)
+    else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = entries
+      .iter()
+      .filter(|it| {
+        !it
+          .get("deprecated")
+          .and_then(Value::as_bool)
+          .unwrap_or(false)
+      })
+      .filter_map(|it| it.get("version").and_then(Value::as_str))
+      .filter_map(|it| Version::parse(it.strip_prefix('v').unwrap_or(it)).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Registry for Helm {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, send};
+use crate::dependency::Dependency;
+
+const JSR_REGISTRY: &str = "https://jsr.io";
+
+/// <https://jsr.io/docs/api#package-metadata>
+pub struct Jsr {
+  url: String,
+}
+
+impl Jsr {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/{name}/meta.json", self.url);
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    let json: Value = serde_json::from_str(&body)?;
+    let Some(versions) = json.get("versions").and_then(Value::as_object) else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = versions
+      .iter()
+      .filter(|(_, meta)| {
+        !meta
+          .get("yanked")
+          .and_then(Value::as_bool)
+          .unwrap_or(false)
+      })
+      .filter_map(|(version, _)| Version::parse(version).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for Jsr {
+  fn default() -> Self {
+    Self { url: JSR_REGISTRY.to_owned() }
+  }
+}
+
+impl Registry for Jsr {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
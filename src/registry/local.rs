@@ -0,0 +1,53 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde::Deserialize;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use super::{Registry, RegistryFuture};
+use crate::dependency::Dependency;
+
+/// A registry backed by a directory with a JSON file per package, such as
+/// `@scope/name.json`, listing its versions as `{ "versions": ["1.0.0"] }`.
+pub struct LocalRegistry {
+  dir: PathBuf,
+}
+
+#[derive(Deserialize)]
+struct LocalPackage {
+  versions: Vec<String>,
+}
+
+impl LocalRegistry {
+  pub fn new(dir: PathBuf) -> Self {
+    Self { dir }
+  }
+
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let path = self.dir.join(format!("{name}.json"));
+    let contents = match tokio::fs::read_to_string(&path).await {
+      Ok(contents) => contents,
+      Err(err) if err.kind() == ErrorKind::NotFound => {
+        bail!("no versions found for {name} at {}", path.display());
+      }
+      Err(err) => return Err(err.into()),
+    };
+
+    let package: LocalPackage = serde_json::from_str(&contents)?;
+    let versions = package
+      .versions
+      .iter()
+      .filter_map(|it| Version::parse(it).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Registry for LocalRegistry {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+use crate::package::manifest::XmlDocument;
+
+const MAVEN_REGISTRY: &str = "https://repo.maven.apache.org/maven2";
+
+/// Overrides the base URL of a Maven repository, e.g. a private Nexus or Artifactory.
+const MAVEN_REGISTRY_ENV: &str = "MIHO_MAVEN_REGISTRY";
+
+/// <https://maven.apache.org/repositories/metadata.html>
+pub struct Maven {
+  url: String,
+}
+
+impl Maven {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let Some((group_id, artifact_id)) = name.split_once(':') else {
+      bail!("invalid maven coordinates: {name}");
+    };
+
+    let group = group_id.replace('.', "/");
+    let url = format!("{}/{group}/{artifact_id}/maven-metadata.xml", self.url);
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    let metadata = XmlDocument::parse(body)?;
+    let versions = metadata
+      .find_all(&["metadata", "versioning", "versions", "version"])
+      .filter_map(|it| Version::parse(metadata.text(it)).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for Maven {
+  fn default() -> Self {
+    Self {
+      url: base_url(MAVEN_REGISTRY_ENV, MAVEN_REGISTRY),
+    }
+  }
+}
+
+impl Registry for Maven {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
@@ -0,0 +1,76 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use reqwest::header::{ACCEPT, AUTHORIZATION};
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, send};
+use crate::dependency::Dependency;
+
+const NPM_REGISTRY: &str = "https://registry.npmjs.org";
+
+/// Dependencies from a registry configured in `.npmrc` are fetched from it instead.
+///
+/// <https://github.com/npm/registry/blob/master/docs/responses/package-metadata.md>
+pub struct Npm {
+  url: String,
+}
+
+impl Npm {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let registry = dependency
+      .registry_url
+      .as_deref()
+      .unwrap_or(&self.url);
+
+    // The slash of a scoped package is escaped, as in `@scope%2fname`.
+    let url = format!("{registry}/{}", name.replace('/', "%2f"));
+    let mut request = HTTP_CLIENT
+      .get(&url)
+      .header(ACCEPT, "application/vnd.npm.install-v1+json");
+
+    if let Some(token) = &dependency.registry_token {
+      request = request.header(AUTHORIZATION, token.as_str());
+    }
+
+    let json: Value = serde_json::from_str(&send(request).await?)?;
+    let Some(versions) = json.get("versions").and_then(Value::as_object) else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = versions
+      .values()
+      .filter_map(parse_version)
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for Npm {
+  fn default() -> Self {
+    Self { url: NPM_REGISTRY.to_owned() }
+  }
+}
+
+impl Registry for Npm {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
+
+fn parse_version(version: &Value) -> Option<Version> {
+  if version
+    .get("deprecated")
+    .and_then(Value::as_str)
+    .is_some_and(|it| !it.is_empty())
+  {
+    return None;
+  }
+
+  version
+    .get("version")
+    .and_then(Value::as_str)
+    .and_then(|it| Version::parse(it).ok())
+}
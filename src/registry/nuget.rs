@@ -0,0 +1,52 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+
+const NUGET_REGISTRY: &str = "https://api.nuget.org/v3-flatcontainer";
+
+/// Overrides the base URL of a `NuGet` flat container, e.g. a private feed.
+const NUGET_REGISTRY_ENV: &str = "MIHO_NUGET_REGISTRY";
+
+/// <https://learn.microsoft.com/nuget/api/package-base-address-resource#enumerate-package-versions>
+pub struct NuGet {
+  url: String,
+}
+
+impl NuGet {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/{}/index.json", self.url, name.to_lowercase());
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    let json: Value = serde_json::from_str(&body)?;
+    let Some(versions) = json.get("versions").and_then(Value::as_array) else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = versions
+      .iter()
+      .filter_map(Value::as_str)
+      .filter_map(|it| Version::parse(it).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for NuGet {
+  fn default() -> Self {
+    Self {
+      url: base_url(NUGET_REGISTRY_ENV, NUGET_REGISTRY),
+    }
+  }
+}
+
+impl Registry for NuGet {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
@@ -0,0 +1,57 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+
+const PACKAGIST_REGISTRY: &str = "https://repo.packagist.org";
+
+/// Overrides the base URL of a Packagist-compatible repository, e.g. a private one.
+const PACKAGIST_REGISTRY_ENV: &str = "MIHO_PACKAGIST_REGISTRY";
+
+/// <https://packagist.org/apidoc#get-package-metadata-v2>
+pub struct Packagist {
+  url: String,
+}
+
+impl Packagist {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/p2/{name}.json", self.url);
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    let json: Value = serde_json::from_str(&body)?;
+    let Some(releases) = json
+      .get("packages")
+      .and_then(|it| it.get(name))
+      .and_then(Value::as_array)
+    else {
+      bail!("no versions found for {name}");
+    };
+
+    // Tags may be prefixed with `v`, while branches are listed as `dev-<branch>`.
+    let versions = releases
+      .iter()
+      .filter_map(|it| it.get("version").and_then(Value::as_str))
+      .filter_map(|it| Version::parse(it.strip_prefix('v').unwrap_or(it)).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for Packagist {
+  fn default() -> Self {
+    Self {
+      url: base_url(PACKAGIST_REGISTRY_ENV, PACKAGIST_REGISTRY),
+    }
+  }
+}
+
+impl Registry for Packagist {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
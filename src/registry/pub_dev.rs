@@ -0,0 +1,63 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use reqwest::header::ACCEPT;
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+
+const PUB_REGISTRY: &str = "https://pub.dev";
+
+/// Same variable the `dart pub` client reads to use a mirror.
+///
+/// <https://dart.dev/tools/pub/environment-variables>
+const PUB_REGISTRY_ENV: &str = "PUB_HOSTED_URL";
+
+/// <https://github.com/dart-lang/pub/blob/master/doc/repository-spec-v2.md>
+pub struct PubDev {
+  url: String,
+}
+
+impl PubDev {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/api/packages/{name}", self.url);
+    let request = HTTP_CLIENT
+      .get(&url)
+      .header(ACCEPT, "application/vnd.pub.v2+json");
+
+    let json: Value = serde_json::from_str(&send(request).await?)?;
+    let Some(versions) = json.get("versions").and_then(Value::as_array) else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = versions
+      .iter()
+      .filter(|it| {
+        !it
+          .get("retracted")
+          .and_then(Value::as_bool)
+          .unwrap_or(false)
+      })
+      .filter_map(|it| it.get("version").and_then(Value::as_str))
+      .filter_map(|it| Version::parse(it).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for PubDev {
+  fn default() -> Self {
+    Self {
+      url: base_url(PUB_REGISTRY_ENV, PUB_REGISTRY),
+    }
+  }
+}
+
+impl Registry for PubDev {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
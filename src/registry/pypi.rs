@@ -0,0 +1,64 @@
+use anyhow::{Result, bail};
+use itertools::Itertools;
+use semver::Version;
+use serde_json::Value;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+use crate::package::manifest::normalize_python_name;
+use crate::version::pep440;
+
+const PYPI_REGISTRY: &str = "https://pypi.org";
+
+/// Overrides the base URL of the `PyPI` JSON API, e.g. to use a local mirror.
+const PYPI_REGISTRY_ENV: &str = "MIHO_PYPI_REGISTRY";
+
+/// <https://docs.pypi.org/api/json/>
+pub struct PyPi {
+  url: String,
+}
+
+impl PyPi {
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let url = format!("{}/pypi/{}/json", self.url, normalize_python_name(name));
+    let body = send(HTTP_CLIENT.get(&url)).await?;
+
+    let json: Value = serde_json::from_str(&body)?;
+    let Some(releases) = json.get("releases").and_then(Value::as_object) else {
+      bail!("no versions found for {name}");
+    };
+
+    let versions = releases
+      .iter()
+      .filter(|(_, files)| !is_yanked(files))
+      .filter_map(|(version, _)| pep440::to_semver(version).ok())
+      .collect_vec();
+
+    Ok(versions)
+  }
+}
+
+impl Default for PyPi {
+  fn default() -> Self {
+    Self {
+      url: base_url(PYPI_REGISTRY_ENV, PYPI_REGISTRY),
+    }
+  }
+}
+
+impl Registry for PyPi {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
+
+/// A release is yanked when all of its files are.
+fn is_yanked(files: &Value) -> bool {
+  files.as_array().is_some_and(|files| {
+    !files.is_empty()
+      && files
+        .iter()
+        .all(|it| it.get("yanked").and_then(Value::as_bool) == Some(true))
+  })
+}
@@ -0,0 +1,227 @@
+use anyhow::Result;
+use reqwest::header::AUTHORIZATION;
+use semver::Version;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::{HTTP_CLIENT, Registry, RegistryFuture, base_url, send};
+use crate::dependency::Dependency;
+use crate::version::parse_partial;
+
+const CRATES_IO_INDEX: &str = "https://index.crates.io";
+
+/// Overrides the root of the crates.io index, e.g. to use a mirror.
+const CRATES_IO_INDEX_ENV: &str = "MIHO_CARGO_REGISTRY";
+
+/// Latest format of the index entries, which newer ones are skipped for, as Cargo does.
+const INDEX_FORMAT: u32 = 2;
+
+/// Dependencies from an alternative registry are fetched from its own index instead.
+///
+/// <https://doc.rust-lang.org/cargo/reference/registry-index.html#sparse-protocol>
+pub struct SparseIndex {
+  url: String,
+}
+
+/// A line of the index file of a crate, describing one of its versions.
+///
+/// <https://doc.rust-lang.org/cargo/reference/registry-index.html#json-schema>
+#[derive(Deserialize)]
+struct IndexEntry {
+  vers: String,
+  #[serde(default)]
+  deps: Vec<IndexDependency>,
+  #[serde(default)]
+  features: HashMap<String, Vec<String>>,
+  features2: Option<HashMap<String, Vec<String>>>,
+  #[serde(default)]
+  yanked: bool,
+  rust_version: Option<String>,
+  v: Option<u32>,
+}
+
+#[derive(Deserialize)]
+struct IndexDependency {
+  name: String,
+  #[serde(default)]
+  optional: bool,
+}
+
+impl SparseIndex {
+  /// Index at `url`, with or without the `sparse+` prefix Cargo uses for it.
+  pub fn new(url: &str) -> Self {
+    let url = url.strip_prefix("sparse+").unwrap_or(url);
+    Self {
+      url: url.trim_end_matches('/').to_owned(),
+    }
+  }
+
+  async fn fetch(&self, dependency: &Dependency) -> Result<Vec<Version>> {
+    let name = dependency.registry_name();
+    let root = dependency
+      .registry_url
+      .as_deref()
+      .unwrap_or(&self.url);
+
+    let url = format!("{root}/{}", index_path(name));
+    let mut request = HTTP_CLIENT.get(&url);
+    if let Some(token) = &dependency.registry_token {
+      request = request.header(AUTHORIZATION, token.as_str());
+    }
+
+    let contents = send(request).await?;
+    Ok(parse_index_file(&contents, dependency))
+  }
+}
+
+impl Default for SparseIndex {
+  fn default() -> Self {
+    Self::new(&base_url(CRATES_IO_INDEX_ENV, CRATES_IO_INDEX))
+  }
+}
+
+impl Registry for SparseIndex {
+  fn versions<'a>(&'a self, dependency: &'a Dependency) -> RegistryFuture<'a> {
+    Box::pin(self.fetch(dependency))
+  }
+}
+
+impl IndexEntry {
+  /// Whether `dependency` could be updated to this version without losing
+  /// any of its features nor raising its minimum Rust version.
+  fn is_compatible(&self, dependency: &Dependency) -> bool {
+    if self.yanked || self.v.unwrap_or(1) > INDEX_FORMAT {
+      return false;
+    }
+
+    let rust_version = self
+      .rust_version
+      .as_deref()
+      .and_then(|it| parse_partial(it).ok());
+
+    if let (Some(required), Some(supported)) = (rust_version, &dependency.rust_version)
+      && required > *supported
+    {
+      return false;
+    }
+
+    dependency
+      .features
+      .iter()
+      .all(|it| self.has_feature(it))
+  }
+
+  /// Optional dependencies are also features, unless another feature refers to them as `dep:name`.
+  fn has_feature(&self, name: &str) -> bool {
+    let features = || {
+      self
+        .features
+        .iter()
+        .chain(self.features2.iter().flatten())
+    };
+
+    if features().any(|(feature, _)| feature == name) {
+      return true;
+    }
+
+    let explicit = format!("dep:{name}");
+    self
+      .deps
+      .iter()
+      .any(|it| it.optional && it.name == name)
+      && !features()
+        .flat_map(|(_, values)| values)
+        .any(|it| *it == explicit)
+  }
+}
+
+/// Versions in the index file of a crate that `dependency` could be updated to.
+/// Lines this version of the index format does not describe are skipped, as Cargo does.
+fn parse_index_file(contents: &str, dependency: &Dependency) -> Vec<Version> {
+  contents
+    .lines()
+    .filter_map(|line| serde_json::from_str::<IndexEntry>(line).ok())
+    .filter(|it| it.is_compatible(dependency))
+    .filter_map(|it| Version::parse(&it.vers).ok())
+    .collect()
+}
+
+/// Path of the index file of a crate, which depends on the length of its name.
+///
+/// <https://doc.rust-lang.org/cargo/reference/registry-index.html#index-files>
+fn index_path(name: &str) -> String {
+  let name = name.to_lowercase();
+  match name.len() {
+    1 => format!("1/{name}"),
+    2 => format!("2/{name}"),
+    3 => format!("3/{}/{name}", &name[..1]),
+    _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::agent::Agent;
+  use crate::dependency::{DependencyKind, DependencyTree};
+  use itertools::Itertools;
+  use semver::Comparator;
+
+  const INDEX_FILE: &str = include_str!(concat!(
+    env!("CARGO_MANIFEST_DIR"),
+    "/fixtures/index/de/mo/demo"
+  ));
+
+  #[test]
+  fn finds_index_paths() {
+    let cases = [
+      ("a", "1/a"),
+      ("ab", "2/ab"),
+      ("abc", "3/a/abc"),
+      ("demo", "de/mo/demo"),
+      ("serde", "se/rd/serde"),
+      ("Serde_JSON", "se/rd/serde_json"),
+    ];
+
+    for (name, expected) in cases {
+      assert_eq!(index_path(name), expected, "{name}");
+    }
+  }
+
+  fn versions(features: &[&str], rust_version: Option<&str>) -> Vec<String> {
+    let mut tree = DependencyTree::new(Agent::Cargo);
+    let comparator = Comparator::parse("^0.1").unwrap();
+    let dependency = tree.add("demo", comparator, DependencyKind::Normal);
+    dependency.features = features.iter().map(ToString::to_string).collect();
+    dependency.rust_version = rust_version.map(|it| parse_partial(it).unwrap());
+
+    parse_index_file(INDEX_FILE, dependency)
+      .iter()
+      .map(ToString::to_string)
+      .collect_vec()
+  }
+
+  #[test]
+  fn skips_malformed_yanked_and_newer_entries() {
+    let expected = ["0.1.0", "0.3.0", "0.5.0", "0.6.0", "0.7.0"];
+    assert_eq!(versions(&[], None), expected);
+  }
+
+  #[test]
+  fn keeps_the_rust_version() {
+    assert_eq!(versions(&[], Some("1.75")), ["0.1.0", "0.3.0", "0.6.0"]);
+    assert_eq!(versions(&[], Some("1.80.0")).len(), 5);
+  }
+
+  #[test]
+  fn keeps_the_features() {
+    assert_eq!(
+      versions(&["std"], None),
+      ["0.1.0", "0.3.0", "0.5.0", "0.7.0"]
+    );
+
+    // Optional dependencies are implicit features, unless a feature refers to them as `dep:`.
+    assert_eq!(versions(&["serde"], None), ["0.3.0", "0.5.0", "0.7.0"]);
+    assert_eq!(versions(&["std", "serde"], Some("1.75")), ["0.3.0"]);
+  }
+}
@@ -0,0 +1,45 @@
+//! Helpers shared by the tests.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// A directory removed once dropped, where tests can lay out their fixtures.
+pub struct TempDir {
+  path: PathBuf,
+}
+
+impl TempDir {
+  pub fn new() -> Self {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+
+    let id = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = format!("miho-test-{}-{id}", std::process::id());
+    let path = std::env::temp_dir().join(name);
+    fs::create_dir_all(&path).unwrap();
+
+    Self { path: path.canonicalize().unwrap() }
+  }
+
+  pub fn path(&self) -> &Path {
+    &self.path
+  }
+
+  /// Writes `contents` to `relative`, creating its parent directories.
+  pub fn write(&self, relative: &str, contents: &str) -> PathBuf {
+    let path = self.path.join(relative);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(&path, contents).unwrap();
+    path
+  }
+
+  pub fn read(&self, relative: &str) -> String {
+    fs::read_to_string(self.path.join(relative)).unwrap()
+  }
+}
+
+impl Drop for TempDir {
+  fn drop(&mut self) {
+    let _ = fs::remove_dir_all(&self.path);
+  }
+}
@@ -1,3 +1,5 @@
+pub mod pep440;
+
 use anyhow::{Result, bail};
 use semver::{BuildMetadata, Comparator, Op, Prerelease, Version, VersionReq};
 
@@ -157,3 +159,19 @@ impl VersionReqExt for VersionReq {
       .any(|c| c.matches(version))
   }
 }
+
+/// Parses a version that may leave out its minor and patch, such as a `rust-version` of `1.70`.
+pub fn parse_partial(version: &str) -> Result<Version> {
+  let parts = version
+    .trim()
+    .split('.')
+    .map(str::parse::<u64>)
+    .collect::<Result<Vec<_>, _>>()?;
+
+  match parts.as_slice() {
+    [major] => Ok(Version::new(*major, 0, 0)),
+    [major, minor] => Ok(Version::new(*major, *minor, 0)),
+    [major, minor, patch] => Ok(Version::new(*major, *minor, *patch)),
+    _ => bail!("invalid version: {version}"),
+  }
+}
@@ -0,0 +1,211 @@
+//! Mapping between [PEP 440](https://peps.python.org/pep-0440/) and semver.
+//!
+//! Pre-releases and development releases become prerelease identifiers
+//! (`1.2rc1` is `1.2.0-rc.1`), while post-releases and local versions
+//! become build metadata, as semver has no equivalent for them.
+
+use anyhow::{Result, bail};
+use semver::{BuildMetadata, Prerelease, Version};
+
+/// Identifier of development releases, which come before any pre-release of the same version.
+/// Semver compares such identifiers in ASCII order, in which digits precede letters.
+const DEV: &str = "0dev";
+
+/// Parses a PEP 440 version, such as `1.2rc1` or `2.0.0.post3`, into semver.
+pub fn to_semver(version: &str) -> Result<Version> {
+  let original = version;
+  let version = version.trim().to_lowercase();
+  let version = version.strip_prefix('v').unwrap_or(&version);
+
+  if version.contains('!') {
+    bail!("version epochs are not supported: {original}");
+  }
+
+  let (public, local) = match version.split_once('+') {
+    Some((public, local)) => (public, Some(local)),
+    None => (version, None),
+  };
+
+  let release_end = public
+    .find(|it: char| !it.is_ascii_digit() && it != '.')
+    .unwrap_or(public.len());
+
+  let release = public[..release_end].trim_end_matches('.');
+  let mut parts = release.split('.').map(str::parse::<u64>);
+  let (Some(Ok(major)), minor, patch, None) =
+    (parts.next(), parts.next(), parts.next(), parts.next())
+  else {
+    bail!("invalid release segment: {original}");
+  };
+
+  let mut pre = Vec::new();
+  let mut build = Vec::new();
+  let mut rest = &public[release_end..];
+
+  while !rest.is_empty() {
+    rest = rest.trim_start_matches(['.', '-', '_']);
+    let label_end = rest
+      .find(|it: char| !it.is_ascii_alphabetic())
+      .unwrap_or(rest.len());
+
+    let (label, tail) = rest.split_at(label_end);
+    let tail = tail.trim_start_matches(['.', '-', '_']);
+    let number_end = tail
+      .find(|it: char| !it.is_ascii_digit())
+      .unwrap_or(tail.len());
+
+    let (number, tail) = tail.split_at(number_end);
+    let number = if number.is_empty() { "0" } else { number };
+
+    match label {
+      "a" | "alpha" => pre.extend(["a", number]),
+      "b" | "beta" => pre.extend(["b", number]),
+      "c" | "rc" | "pre" | "preview" => pre.extend(["rc", number]),
+      "dev" => pre.extend([DEV, number]),
+      "post" | "rev" | "r" | "" => build.extend(["post", number]),
+      _ => bail!("invalid version suffix `{label}`: {original}"),
+    }
+
+    rest = tail;
+  }
+
+  let local = local.map(|it| it.replace(['_', '-'], "."));
+  build.extend(local.as_deref());
+
+  let mut semver = Version::new(major, minor.unwrap_or(Ok(0))?, patch.unwrap_or(Ok(0))?);
+  semver.pre = Prerelease::new(&pre.join("."))?;
+  semver.build = BuildMetadata::new(&build.join("."))?;
+
+  Ok(semver)
+}
+
+/// Renders a semver version the way PEP 440 expects, e.g. `1.2.0-rc.1` becomes `1.2.0rc1`.
+pub fn from_semver(version: &Version) -> Result<String> {
+  let mut output = format!("{}.{}.{}", version.major, version.minor, version.patch);
+
+  let pre = version.pre.as_str();
+  let mut identifiers = pre
+    .split('.')
+    .filter(|it| !it.is_empty())
+    .peekable();
+
+  while let Some(label) = identifiers.next() {
+    let number = identifiers
+      .next_if(|it| it.bytes().all(|b| b.is_ascii_digit()))
+      .unwrap_or("0");
+
+    let prefix = match label {
+      "a" | "alpha" => "a",
+      "b" | "beta" => "b",
+      "c" | "rc" | "pre" | "preview" => "rc",
+      DEV | "dev" => ".dev",
+      _ => bail!("prerelease `{pre}` has no PEP 440 equivalent"),
+    };
+
+    output.push_str(prefix);
+    output.push_str(number);
+  }
+
+  let build = version.build.as_str();
+  let local = match build.strip_prefix("post.") {
+    Some(rest) => {
+      let (number, local) = rest.split_once('.').unwrap_or((rest, ""));
+      output.push_str(".post");
+      output.push_str(number);
+      local
+    }
+    None => build,
+  };
+
+  if !local.is_empty() {
+    output.push('+');
+    output.push_str(local);
+  }
+
+  Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_versions() {
+    let cases = [
+      ("1", "1.0.0"),
+      ("1.2", "1.2.0"),
+      ("v1.2.3", "1.2.3"),
+      ("1.2rc1", "1.2.0-rc.1"),
+      ("1.2.0-RC.1", "1.2.0-rc.1"),
+      ("1.0a", "1.0.0-a.0"),
+      ("1.0.0b2.dev3", "1.0.0-b.2.0dev.3"),
+      ("1.0.dev2", "1.0.0-0dev.2"),
+      ("2.0.0.post3", "2.0.0+post.3"),
+      ("1.0-1", "1.0.0+post.1"),
+      ("1.0+ubuntu-1", "1.0.0+ubuntu.1"),
+      ("1.0.post1+local_2", "1.0.0+post.1.local.2"),
+    ];
+
+    for (pep440, semver) in cases {
+      assert_eq!(to_semver(pep440).unwrap().to_string(), semver, "{pep440}");
+    }
+  }
+
+  #[test]
+  fn keeps_the_order_of_pep440() {
+    let versions = [
+      "1.0.dev1", "1.0.dev2", "1.0a1", "1.0a2", "1.0b1", "1.0rc1", "1.0", "1.1.dev1", "1.1a1",
+    ];
+
+    for pair in versions.windows(2) {
+      let (a, b) = (to_semver(pair[0]).unwrap(), to_semver(pair[1]).unwrap());
+      assert!(a < b, "{} < {}", pair[0], pair[1]);
+    }
+  }
+
+  #[test]
+  fn rejects_unsupported_versions() {
+    for version in ["1!2.0", "1.2.3.4", "1.0foo", "latest"] {
+      assert!(to_semver(version).is_err(), "{version}");
+    }
+  }
+
+  #[test]
+  fn renders_versions() {
+    let cases = [
+      ("1.2.0", "1.2.0"),
+      ("1.2.0-rc.1", "1.2.0rc1"),
+      ("1.2.0-alpha", "1.2.0a0"),
+      ("1.0.0-b.2.0dev.3", "1.0.0b2.dev3"),
+      ("1.0.0-dev.1", "1.0.0.dev1"),
+      ("2.0.0+post.3", "2.0.0.post3"),
+      ("1.0.0+post.1.local.2", "1.0.0.post1+local.2"),
+      ("1.0.0+local", "1.0.0+local"),
+    ];
+
+    for (semver, pep440) in cases {
+      let version = Version::parse(semver).unwrap();
+      assert_eq!(from_semver(&version).unwrap(), pep440, "{semver}");
+    }
+  }
+
+  #[test]
+  fn rejects_unknown_prereleases() {
+    let version = Version::parse("1.0.0-nightly.1").unwrap();
+    assert!(from_semver(&version).is_err());
+  }
+
+  #[test]
+  fn round_trips() {
+    for version in [
+      "1.2.3",
+      "1.2.3rc1",
+      "1.2.3a4.dev5",
+      "1.2.3.post6",
+      "1.2.3+local.7",
+    ] {
+      let semver = to_semver(version).unwrap();
+      assert_eq!(from_semver(&semver).unwrap(), version);
+    }
+  }
+}